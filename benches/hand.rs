@@ -0,0 +1,86 @@
+//! Quest affordability with a `Vec<ResourceCard>` hand against a `CompactHand`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use pawlice_academy::bears::models::*;
+
+fn hands(size: usize) -> Vec<Vec<ResourceCard>> {
+    let mut rng = StdRng::seed_from_u64(size as u64);
+    (0..64)
+        .map(|_| {
+            let mut resources = build_all_resources();
+            resources.shuffle(&mut rng);
+            resources.truncate(size);
+            resources
+        })
+        .collect()
+}
+
+fn affordability(c: &mut Criterion) {
+    let (quests, _) = build_all_quests();
+    let costs: Vec<CompactHand> = quests.iter().map(|q| q.compact_cost()).collect();
+    let mut group = c.benchmark_group("affordability");
+    for size in [5, 20, 60] {
+        let vec_hands = hands(size);
+        let compact_hands: Vec<CompactHand> = vec_hands
+            .iter()
+            .map(|h| h.iter().copied().collect())
+            .collect();
+        group.bench_with_input(BenchmarkId::new("vec", size), &vec_hands, |b, hands| {
+            b.iter(|| {
+                hands
+                    .iter()
+                    .flat_map(|h| quests.iter().map(move |q| q.can_be_paid_with(h)))
+                    .filter(|affordable| *affordable)
+                    .count()
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("compact", size),
+            &compact_hands,
+            |b, hands| {
+                b.iter(|| {
+                    hands
+                        .iter()
+                        .flat_map(|h| costs.iter().map(move |c| h.covers(c)))
+                        .filter(|affordable| *affordable)
+                        .count()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn add_and_remove(c: &mut Criterion) {
+    let cards = build_all_resources();
+    let mut group = c.benchmark_group("add_and_remove");
+    group.bench_function("vec", |b| {
+        b.iter(|| {
+            let mut hand: Vec<ResourceCard> = Vec::new();
+            hand.extend(cards.iter().copied());
+            for card in &cards {
+                let i = hand.iter().position(|c| c == card).unwrap();
+                hand.swap_remove(i);
+            }
+            black_box(hand.len())
+        })
+    });
+    group.bench_function("compact", |b| {
+        b.iter(|| {
+            let mut hand = CompactHand::new();
+            for card in &cards {
+                hand.add(*card);
+            }
+            for card in &cards {
+                hand.remove(*card);
+            }
+            black_box(hand.count(ResourceCard::Ingredient(IngredientKind::Fish)))
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, affordability, add_and_remove);
+criterion_main!(benches);
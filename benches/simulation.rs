@@ -0,0 +1,160 @@
+//! Games per second for table setup and for full games between random agents, along with the
+//! number of heap allocations each of them makes.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use pawlice_academy::bears::models::{build_all_quests, build_all_resources};
+use pawlice_academy::bears::rules::GameRules;
+use pawlice_academy::bears::system::{
+    GameState, GameTable, InitialPlayerSelectionStrategy, QuestTileSelectionStrategy,
+};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn new_table(num_players: usize, seed: u64) -> GameTable {
+    let mut game_table = GameTable::from_seed(
+        num_players,
+        GameRules::default(),
+        InitialPlayerSelectionStrategy::Random,
+        QuestTileSelectionStrategy::Random,
+        seed,
+    )
+    .unwrap();
+    game_table.check_invariants = false;
+    game_table
+}
+
+fn set_up(num_players: usize, seed: u64) -> GameTable {
+    let mut game_table = new_table(num_players, seed);
+    game_table.play_one_step();
+    game_table
+}
+
+fn play(num_players: usize, seed: u64) -> GameTable {
+    let mut game_table = new_table(num_players, seed);
+    game_table.play_until_done();
+    game_table
+}
+
+/// Average allocations and allocated bytes of `f` over `runs` runs.
+fn allocations_of(runs: u64, mut f: impl FnMut(u64)) -> (f64, f64) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    for run in 0..runs {
+        f(run);
+    }
+    (
+        (ALLOCATIONS.load(Ordering::Relaxed) - allocations) as f64 / runs as f64,
+        (ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes) as f64 / runs as f64,
+    )
+}
+
+fn report_allocations() {
+    let runs = 200;
+    let measurements = [
+        (
+            "build_all_quests",
+            allocations_of(runs, |_| drop(black_box(build_all_quests()))),
+        ),
+        (
+            "build_all_resources",
+            allocations_of(runs, |_| drop(black_box(build_all_resources()))),
+        ),
+        (
+            "setup/2",
+            allocations_of(runs, |seed| drop(black_box(set_up(2, seed)))),
+        ),
+        (
+            "setup/6",
+            allocations_of(runs, |seed| drop(black_box(set_up(6, seed)))),
+        ),
+        (
+            "full_game/2",
+            allocations_of(runs, |seed| drop(black_box(play(2, seed)))),
+        ),
+        (
+            "full_game/6",
+            allocations_of(runs, |seed| drop(black_box(play(6, seed)))),
+        ),
+    ];
+    println!(
+        "{:<22} {:>12} {:>14}",
+        "allocations per run", "count", "bytes"
+    );
+    for (name, (allocations, bytes)) in measurements {
+        println!("{:<22} {:>12.1} {:>14.0}", name, allocations, bytes);
+    }
+}
+
+fn setup(c: &mut Criterion) {
+    report_allocations();
+
+    let mut group = c.benchmark_group("setup");
+    group.bench_function("build_all_quests", |b| b.iter(build_all_quests));
+    group.bench_function("build_all_resources", |b| b.iter(build_all_resources));
+    for num_players in [2, 4, 6] {
+        group.throughput(Throughput::Elements(1));
+        group.bench_with_input(
+            BenchmarkId::new("table", num_players),
+            &num_players,
+            |b, &num_players| {
+                let mut seed = 0;
+                b.iter(|| {
+                    seed += 1;
+                    set_up(num_players, seed)
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn full_game(c: &mut Criterion) {
+    let mut group = c.benchmark_group("full_game");
+    for num_players in [2, 4, 6] {
+        group.throughput(Throughput::Elements(1));
+        group.bench_with_input(
+            BenchmarkId::new("random_agents", num_players),
+            &num_players,
+            |b, &num_players| {
+                let mut seed = 0;
+                b.iter(|| {
+                    seed += 1;
+                    let game_table = play(num_players, seed);
+                    assert!(matches!(game_table.state, GameState::Done { .. }));
+                    game_table
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, setup, full_game);
+criterion_main!(benches);
@@ -0,0 +1,12 @@
+//! Generates `pawlice.h` from the exported functions into `OUT_DIR`. The copy C clients use is
+//! checked in as `include/pawlice.h`, and a test fails when it falls behind.
+
+fn main() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("Cargo sets the manifest dir");
+    let out_dir = std::env::var("OUT_DIR").expect("Cargo sets the out dir");
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    cbindgen::generate(&crate_dir)
+        .expect("The C API can be described in a header")
+        .write_to_file(format!("{}/pawlice.h", out_dir));
+}
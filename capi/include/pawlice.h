@@ -0,0 +1,156 @@
+#ifndef PAWLICE_H
+#define PAWLICE_H
+
+/* Generated by cbindgen from capi/src/lib.rs. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The values of `PawliceAction::kind`.
+ */
+enum PawliceActionKind {
+  PAWLICE_ACTION_KIND_DRAW_RESOURCE = 0,
+  PAWLICE_ACTION_KIND_COMPLETE_QUEST_TILE = 1,
+  PAWLICE_ACTION_KIND_COMPLETE_OPEN_QUEST = 2,
+  PAWLICE_ACTION_KIND_PASS = 3,
+};
+typedef uint32_t PawliceActionKind;
+
+/**
+ * A game table. Only ever handled through pointers.
+ */
+typedef struct PawliceTable PawliceTable;
+
+/**
+ * The setup numbers of `GameRules` that a C client may change. Start from
+ * `pawlice_rules_default`.
+ */
+typedef struct PawliceRules {
+  size_t min_players;
+  size_t max_players;
+  size_t starting_resources;
+  size_t quest_tiles_per_player;
+  size_t open_quests;
+  /**
+   * 0 for no turn limit.
+   */
+  size_t turn_limit;
+} PawliceRules;
+
+/**
+ * An action. `index` is the quest tile or open quest index, and is ignored for the others.
+ */
+typedef struct PawliceAction {
+  /**
+   * One of the `PawliceActionKind` values. Kept as a plain integer so that a C client
+   * passing any other value gets an error rather than undefined behavior.
+   */
+  uint32_t kind;
+  size_t index;
+} PawliceAction;
+
+/**
+ * The standard rules.
+ */
+struct PawliceRules pawlice_rules_default(void);
+
+/**
+ * A table for `num_players` players dealt from `seed`, ready for the first turn. `rules` may
+ * be null for the standard rules. Returns null if the rules are invalid or do not allow
+ * `num_players` players.
+ *
+ * # Safety
+ *
+ * `rules` must be null or point to a valid `PawliceRules`.
+ */
+struct PawliceTable *pawlice_table_new(size_t num_players,
+                                       uint64_t seed,
+                                       const struct PawliceRules *rules);
+
+/**
+ * # Safety
+ *
+ * `table` must be null or come from `pawlice_table_new`, and must not be used afterwards.
+ */
+void pawlice_table_free(struct PawliceTable *table);
+
+/**
+ * # Safety
+ *
+ * `string` must be null or come from this library, and must not be used afterwards.
+ */
+void pawlice_string_free(char *string);
+
+/**
+ * The whole table as JSON, hidden cards and deck order included, with the legal actions of
+ * the current player.
+ *
+ * # Safety
+ *
+ * `table` must be null or a live table from `pawlice_table_new`.
+ */
+char *pawlice_table_state_json(const struct PawliceTable *table);
+
+/**
+ * What the player with index `player_index` can see, as JSON, or null if there is no such
+ * player.
+ *
+ * # Safety
+ *
+ * `table` must be null or a live table from `pawlice_table_new`.
+ */
+char *pawlice_table_view_json(const struct PawliceTable *table, size_t player_index);
+
+/**
+ * Index of the player whose turn it is, or -1 once the game is done.
+ *
+ * # Safety
+ *
+ * `table` must be null or a live table from `pawlice_table_new`.
+ */
+int32_t pawlice_table_current_player(const struct PawliceTable *table);
+
+/**
+ * # Safety
+ *
+ * `table` must be null or a live table from `pawlice_table_new`.
+ */
+bool pawlice_table_is_done(const struct PawliceTable *table);
+
+/**
+ * Writes up to `capacity` legal actions of the current player to `actions` and returns how
+ * many there are in total, so a call with a capacity of 0 sizes the buffer.
+ *
+ * # Safety
+ *
+ * `table` must be null or a live table from `pawlice_table_new`, and `actions` must have room
+ * for `capacity` actions.
+ */
+size_t pawlice_table_legal_actions(const struct PawliceTable *table,
+                                   struct PawliceAction *actions,
+                                   size_t capacity);
+
+/**
+ * Plays `action` for the current player. Returns false, leaving the table untouched, if the
+ * action is not legal or its kind is not a `PawliceActionKind`.
+ *
+ * # Safety
+ *
+ * `table` must be null or a live table from `pawlice_table_new`.
+ */
+bool pawlice_table_apply_action(struct PawliceTable *table, struct PawliceAction action);
+
+/**
+ * Score of the player with index `player_index`, or 0 if there is no such player.
+ *
+ * # Safety
+ *
+ * `table` must be null or a live table from `pawlice_table_new`.
+ */
+size_t pawlice_table_score(const struct PawliceTable *table, size_t player_index);
+
+#endif  /* PAWLICE_H */
@@ -0,0 +1,300 @@
+//! A C ABI for embedding the game engine. `include/pawlice.h` is generated from this file by
+//! the build script and checked in for C clients.
+//!
+//! Tables are created with `pawlice_table_new` and released with `pawlice_table_free`. Strings
+//! returned by the library are NUL-terminated UTF-8 JSON and must be released with
+//! `pawlice_string_free`. The JSON is the same the game server and recordings use. Every
+//! function accepts a null table and then does nothing.
+
+use pawlice_academy::bears::rules::GameRules;
+use pawlice_academy::bears::system::{
+    Action, GameState, GameTable, InitialPlayerSelectionStrategy, Player,
+    QuestTileSelectionStrategy,
+};
+use pawlice_academy::bears::view::CasterView;
+use serde::Serialize;
+use std::ffi::{c_char, CString};
+
+/// A game table. Only ever handled through pointers.
+pub struct PawliceTable {
+    table: GameTable,
+}
+
+/// The setup numbers of `GameRules` that a C client may change. Start from
+/// `pawlice_rules_default`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PawliceRules {
+    pub min_players: usize,
+    pub max_players: usize,
+    pub starting_resources: usize,
+    pub quest_tiles_per_player: usize,
+    pub open_quests: usize,
+    /// 0 for no turn limit.
+    pub turn_limit: usize,
+}
+
+/// The values of `PawliceAction::kind`.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PawliceActionKind {
+    DrawResource = 0,
+    CompleteQuestTile = 1,
+    CompleteOpenQuest = 2,
+    Pass = 3,
+}
+
+/// An action. `index` is the quest tile or open quest index, and is ignored for the others.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PawliceAction {
+    /// One of the `PawliceActionKind` values. Kept as a plain integer so that a C client
+    /// passing any other value gets an error rather than undefined behavior.
+    pub kind: u32,
+    pub index: usize,
+}
+
+/// The whole table, as `pawlice_table_state_json` writes it.
+#[derive(Serialize)]
+struct TableJson {
+    #[serde(flatten)]
+    table: CasterView,
+    legal_actions: Vec<Action>,
+}
+
+impl From<Action> for PawliceAction {
+    fn from(action: Action) -> Self {
+        let (kind, index) = match action {
+            Action::DrawResource => (PawliceActionKind::DrawResource, 0),
+            Action::CompleteQuestTile { tile_index } => {
+                (PawliceActionKind::CompleteQuestTile, tile_index)
+            }
+            Action::CompleteOpenQuest { quest_index } => {
+                (PawliceActionKind::CompleteOpenQuest, quest_index)
+            }
+            Action::Pass => (PawliceActionKind::Pass, 0),
+        };
+        Self {
+            kind: kind as u32,
+            index,
+        }
+    }
+}
+
+impl TryFrom<PawliceAction> for Action {
+    type Error = String;
+
+    fn try_from(action: PawliceAction) -> Result<Self, Self::Error> {
+        let kinds = [
+            PawliceActionKind::DrawResource,
+            PawliceActionKind::CompleteQuestTile,
+            PawliceActionKind::CompleteOpenQuest,
+            PawliceActionKind::Pass,
+        ];
+        match kinds.into_iter().find(|&kind| kind as u32 == action.kind) {
+            Some(PawliceActionKind::DrawResource) => Ok(Action::DrawResource),
+            Some(PawliceActionKind::CompleteQuestTile) => Ok(Action::CompleteQuestTile {
+                tile_index: action.index,
+            }),
+            Some(PawliceActionKind::CompleteOpenQuest) => Ok(Action::CompleteOpenQuest {
+                quest_index: action.index,
+            }),
+            Some(PawliceActionKind::Pass) => Ok(Action::Pass),
+            None => Err(format!("unknown action kind {}", action.kind)),
+        }
+    }
+}
+
+/// The standard rules.
+#[no_mangle]
+pub extern "C" fn pawlice_rules_default() -> PawliceRules {
+    let rules = GameRules::default();
+    PawliceRules {
+        min_players: rules.min_players,
+        max_players: rules.max_players,
+        starting_resources: rules.starting_resources,
+        quest_tiles_per_player: rules.quest_tiles_per_player,
+        open_quests: rules.open_quests,
+        turn_limit: rules.turn_limit.unwrap_or(0),
+    }
+}
+
+/// A table for `num_players` players dealt from `seed`, ready for the first turn. `rules` may
+/// be null for the standard rules. Returns null if the rules are invalid or do not allow
+/// `num_players` players.
+///
+/// # Safety
+///
+/// `rules` must be null or point to a valid `PawliceRules`.
+#[no_mangle]
+pub unsafe extern "C" fn pawlice_table_new(
+    num_players: usize,
+    seed: u64,
+    rules: *const PawliceRules,
+) -> *mut PawliceTable {
+    let mut game_rules = GameRules::default();
+    if let Some(rules) = rules.as_ref() {
+        game_rules.min_players = rules.min_players;
+        game_rules.max_players = rules.max_players;
+        game_rules.starting_resources = rules.starting_resources;
+        game_rules.quest_tiles_per_player = rules.quest_tiles_per_player;
+        game_rules.open_quests = rules.open_quests;
+        game_rules.turn_limit = Some(rules.turn_limit).filter(|&limit| limit > 0);
+    }
+    match GameTable::from_seed(
+        num_players,
+        game_rules,
+        InitialPlayerSelectionStrategy::Random,
+        QuestTileSelectionStrategy::Random,
+        seed,
+    ) {
+        Some(mut table) => {
+            table.play_one_step();
+            Box::into_raw(Box::new(PawliceTable { table }))
+        }
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// # Safety
+///
+/// `table` must be null or come from `pawlice_table_new`, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn pawlice_table_free(table: *mut PawliceTable) {
+    if !table.is_null() {
+        drop(Box::from_raw(table));
+    }
+}
+
+/// # Safety
+///
+/// `string` must be null or come from this library, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn pawlice_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// The whole table as JSON, hidden cards and deck order included, with the legal actions of
+/// the current player.
+///
+/// # Safety
+///
+/// `table` must be null or a live table from `pawlice_table_new`.
+#[no_mangle]
+pub unsafe extern "C" fn pawlice_table_state_json(table: *const PawliceTable) -> *mut c_char {
+    let Some(table) = table.as_ref().map(|t| &t.table) else {
+        return std::ptr::null_mut();
+    };
+    to_c_string(&TableJson {
+        table: table.caster_view(),
+        legal_actions: table.legal_actions(),
+    })
+}
+
+/// What the player with index `player_index` can see, as JSON, or null if there is no such
+/// player.
+///
+/// # Safety
+///
+/// `table` must be null or a live table from `pawlice_table_new`.
+#[no_mangle]
+pub unsafe extern "C" fn pawlice_table_view_json(
+    table: *const PawliceTable,
+    player_index: usize,
+) -> *mut c_char {
+    let Some(table) = table.as_ref().map(|t| &t.table) else {
+        return std::ptr::null_mut();
+    };
+    if !table.players.iter().any(|p| p.index == player_index) {
+        return std::ptr::null_mut();
+    }
+    to_c_string(&table.view_for(player_index))
+}
+
+/// Index of the player whose turn it is, or -1 once the game is done.
+///
+/// # Safety
+///
+/// `table` must be null or a live table from `pawlice_table_new`.
+#[no_mangle]
+pub unsafe extern "C" fn pawlice_table_current_player(table: *const PawliceTable) -> i32 {
+    table
+        .as_ref()
+        .and_then(|t| t.table.current_player_index())
+        .map_or(-1, |index| index as i32)
+}
+
+/// # Safety
+///
+/// `table` must be null or a live table from `pawlice_table_new`.
+#[no_mangle]
+pub unsafe extern "C" fn pawlice_table_is_done(table: *const PawliceTable) -> bool {
+    table
+        .as_ref()
+        .is_some_and(|t| matches!(t.table.state, GameState::Done { .. }))
+}
+
+/// Writes up to `capacity` legal actions of the current player to `actions` and returns how
+/// many there are in total, so a call with a capacity of 0 sizes the buffer.
+///
+/// # Safety
+///
+/// `table` must be null or a live table from `pawlice_table_new`, and `actions` must have room
+/// for `capacity` actions.
+#[no_mangle]
+pub unsafe extern "C" fn pawlice_table_legal_actions(
+    table: *const PawliceTable,
+    actions: *mut PawliceAction,
+    capacity: usize,
+) -> usize {
+    let Some(table) = table.as_ref() else {
+        return 0;
+    };
+    let legal_actions = table.table.legal_actions();
+    for (i, action) in legal_actions.iter().take(capacity).enumerate() {
+        actions.add(i).write((*action).into());
+    }
+    legal_actions.len()
+}
+
+/// Plays `action` for the current player. Returns false, leaving the table untouched, if the
+/// action is not legal or its kind is not a `PawliceActionKind`.
+///
+/// # Safety
+///
+/// `table` must be null or a live table from `pawlice_table_new`.
+#[no_mangle]
+pub unsafe extern "C" fn pawlice_table_apply_action(
+    table: *mut PawliceTable,
+    action: PawliceAction,
+) -> bool {
+    let Ok(action) = Action::try_from(action) else {
+        return false;
+    };
+    table.as_mut().is_some_and(|t| t.table.apply_action(action))
+}
+
+/// Score of the player with index `player_index`, or 0 if there is no such player.
+///
+/// # Safety
+///
+/// `table` must be null or a live table from `pawlice_table_new`.
+#[no_mangle]
+pub unsafe extern "C" fn pawlice_table_score(
+    table: *const PawliceTable,
+    player_index: usize,
+) -> usize {
+    table
+        .as_ref()
+        .and_then(|t| t.table.players.iter().find(|p| p.index == player_index))
+        .map_or(0, Player::score)
+}
+
+fn to_c_string(value: &impl Serialize) -> *mut c_char {
+    let json = serde_json::to_string(value).expect("Tables always serialize");
+    CString::new(json)
+        .expect("JSON never contains NUL")
+        .into_raw()
+}
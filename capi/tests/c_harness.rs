@@ -0,0 +1,49 @@
+//! Compiles `harness.c` against the static library and runs it, and checks that the header
+//! C clients use is the one the build script generates.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn c_harness_plays_complete_games() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // Test binaries live in `target/<profile>/deps`, next to the libraries in `target/<profile>`.
+    let profile_dir = std::env::current_exe()
+        .expect("The test knows where it runs from")
+        .parent()
+        .and_then(|deps| deps.parent())
+        .expect("Test binaries live two levels below the target dir")
+        .to_path_buf();
+    let harness = profile_dir.join("pawlice_c_harness");
+
+    let compiled = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(manifest_dir.join("tests/harness.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(profile_dir.join("libpawlice_capi.a"))
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&harness)
+        .status()
+        .expect("A C compiler is installed");
+    assert!(compiled.success(), "harness.c did not compile");
+
+    let output = Command::new(&harness).output().expect("The harness runs");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn checked_in_header_is_up_to_date() {
+    let generated = PathBuf::from(env!("OUT_DIR")).join("pawlice.h");
+    let checked_in = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("include/pawlice.h");
+    let read = |path: &PathBuf| std::fs::read_to_string(path).unwrap_or_default();
+    assert!(
+        read(&generated) == read(&checked_in),
+        "{} is out of date, copy {} over it",
+        checked_in.display(),
+        generated.display()
+    );
+}
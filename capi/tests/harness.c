@@ -0,0 +1,89 @@
+/* Plays complete games through the C API. Exits with 0 when every check passes. */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "pawlice.h"
+
+#define CHECK(condition)                                              \
+  do {                                                                \
+    if (!(condition)) {                                               \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, \
+              #condition);                                            \
+      return 1;                                                       \
+    }                                                                 \
+  } while (0)
+
+/* Plays a game to the end, completing quests whenever possible. */
+static int play_game(size_t num_players, uint64_t seed, const PawliceRules *rules) {
+  PawliceTable *table = pawlice_table_new(num_players, seed, rules);
+  CHECK(table != NULL);
+
+  size_t turns = 0;
+  while (!pawlice_table_is_done(table)) {
+    int32_t player = pawlice_table_current_player(table);
+    CHECK(player >= 0 && (size_t)player < num_players);
+
+    char *view = pawlice_table_view_json(table, (size_t)player);
+    CHECK(view != NULL);
+    CHECK(strstr(view, "\"legal_actions\"") != NULL);
+    pawlice_string_free(view);
+
+    PawliceAction actions[16];
+    size_t count = pawlice_table_legal_actions(table, actions, 16);
+    CHECK(count > 0 && count <= 16);
+    PawliceAction chosen = actions[0];
+    for (size_t i = 0; i < count; i++) {
+      if (actions[i].kind == PAWLICE_ACTION_KIND_COMPLETE_QUEST_TILE ||
+          actions[i].kind == PAWLICE_ACTION_KIND_COMPLETE_OPEN_QUEST) {
+        chosen = actions[i];
+      }
+    }
+    CHECK(pawlice_table_apply_action(table, chosen));
+    turns++;
+    CHECK(turns < 100000);
+  }
+
+  CHECK(pawlice_table_current_player(table) == -1);
+  CHECK(pawlice_table_legal_actions(table, NULL, 0) == 0);
+  size_t total = 0;
+  for (size_t i = 0; i < num_players; i++) {
+    total += pawlice_table_score(table, i);
+  }
+  CHECK(total > 0);
+
+  char *state = pawlice_table_state_json(table);
+  CHECK(state != NULL);
+  CHECK(strstr(state, "\"phase\":\"done\"") != NULL);
+  pawlice_string_free(state);
+
+  pawlice_table_free(table);
+  return 0;
+}
+
+int main(void) {
+  PawliceRules rules = pawlice_rules_default();
+  CHECK(play_game(2, 1, NULL) == 0);
+  CHECK(play_game(4, 2, &rules) == 0);
+
+  rules.starting_resources = 7;
+  rules.turn_limit = 20;
+  CHECK(play_game(3, 3, &rules) == 0);
+
+  /* Illegal requests are refused rather than crashing. */
+  CHECK(pawlice_table_new(9, 0, NULL) == NULL);
+  rules.starting_resources = 1000;
+  CHECK(pawlice_table_new(2, 0, &rules) == NULL);
+  PawliceTable *table = pawlice_table_new(2, 4, NULL);
+  PawliceAction missing = {PAWLICE_ACTION_KIND_COMPLETE_OPEN_QUEST, 99};
+  CHECK(!pawlice_table_apply_action(table, missing));
+  PawliceAction unknown = {42, 0};
+  CHECK(!pawlice_table_apply_action(table, unknown));
+  CHECK(pawlice_table_view_json(table, 7) == NULL);
+  pawlice_table_free(table);
+  pawlice_table_free(NULL);
+  pawlice_string_free(NULL);
+
+  printf("C harness passed\n");
+  return 0;
+}
@@ -0,0 +1,314 @@
+//! Python bindings for the game engine, built with maturin into the `pawlice` module.
+
+// The `#[pymethods]` expansion of PyO3 0.22 converts `PyErr` into itself.
+#![allow(clippy::useless_conversion)]
+
+use pawlice_academy::bears::{env, rules::GameRules, system};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// One of the actions of `pawlice_academy::bears::system::Action`.
+#[pyclass(frozen, eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Action {
+    inner: system::Action,
+}
+
+#[pymethods]
+impl Action {
+    #[staticmethod]
+    fn draw_resource() -> Self {
+        system::Action::DrawResource.into()
+    }
+
+    #[staticmethod]
+    fn complete_quest_tile(tile_index: usize) -> Self {
+        system::Action::CompleteQuestTile { tile_index }.into()
+    }
+
+    #[staticmethod]
+    fn complete_open_quest(quest_index: usize) -> Self {
+        system::Action::CompleteOpenQuest { quest_index }.into()
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "pass_")]
+    fn pass() -> Self {
+        system::Action::Pass.into()
+    }
+
+    /// `draw_resource`, `complete_quest_tile`, `complete_open_quest` or `pass`.
+    #[getter]
+    fn kind(&self) -> &'static str {
+        match self.inner {
+            system::Action::DrawResource => "draw_resource",
+            system::Action::CompleteQuestTile { .. } => "complete_quest_tile",
+            system::Action::CompleteOpenQuest { .. } => "complete_open_quest",
+            system::Action::Pass => "pass",
+        }
+    }
+
+    /// The quest tile or open quest index, if the action has one.
+    #[getter]
+    fn index(&self) -> Option<usize> {
+        match self.inner {
+            system::Action::CompleteQuestTile { tile_index } => Some(tile_index),
+            system::Action::CompleteOpenQuest { quest_index } => Some(quest_index),
+            _ => None,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.inner)
+    }
+}
+
+impl From<system::Action> for Action {
+    fn from(inner: system::Action) -> Self {
+        Self { inner }
+    }
+}
+
+/// A full game, driven step by step or played out by the players' agents.
+#[pyclass]
+struct GameTable {
+    inner: system::GameTable,
+}
+
+#[pymethods]
+impl GameTable {
+    /// Every seat is played by `strategy`, one of `random` or `greedy`, until changed with
+    /// `set_strategy`. The deal is random unless `seed` is given.
+    #[new]
+    #[pyo3(signature = (num_players, seed=None, strategy="random"))]
+    fn new(num_players: usize, seed: Option<u64>, strategy: &str) -> PyResult<Self> {
+        let strategy = parse_strategy(strategy)?;
+        let mut inner = system::GameTable::from_seed(
+            num_players,
+            GameRules::default(),
+            system::InitialPlayerSelectionStrategy::Random,
+            system::QuestTileSelectionStrategy::Random,
+            seed.unwrap_or_else(rand::random),
+        )
+        .ok_or_else(|| {
+            PyValueError::new_err(format!("Invalid number of players: {}", num_players))
+        })?;
+        for player in inner.players.iter_mut() {
+            player.strategy = strategy.clone();
+        }
+        inner.play_one_step();
+        Ok(Self { inner })
+    }
+
+    fn set_strategy(&mut self, player_index: usize, strategy: &str) -> PyResult<()> {
+        let strategy = parse_strategy(strategy)?;
+        match self
+            .inner
+            .players
+            .iter_mut()
+            .find(|p| p.index == player_index)
+        {
+            Some(player) => {
+                player.strategy = strategy;
+                Ok(())
+            }
+            None => Err(PyValueError::new_err(format!(
+                "No player with index {}",
+                player_index
+            ))),
+        }
+    }
+
+    /// Lets the current player's agent take one turn.
+    fn play_one_step(&mut self) {
+        self.inner.play_one_step();
+    }
+
+    fn play_until_done(&mut self, py: Python<'_>) {
+        py.allow_threads(|| self.inner.play_until_done());
+    }
+
+    fn legal_actions(&self) -> Vec<Action> {
+        self.inner
+            .legal_actions()
+            .into_iter()
+            .map(Action::from)
+            .collect()
+    }
+
+    /// Plays `action` for the current player, returning whether it was legal.
+    fn apply_action(&mut self, action: Action) -> bool {
+        self.inner.apply_action(action.inner)
+    }
+
+    /// The player whose turn it is, or `None` once the game is done.
+    #[getter]
+    fn current_player(&self) -> Option<usize> {
+        self.inner.current_player_index()
+    }
+
+    #[getter]
+    fn done(&self) -> bool {
+        matches!(self.inner.state, system::GameState::Done { .. })
+    }
+
+    /// Scores by player index.
+    fn scores(&self) -> Vec<usize> {
+        scores(&self.inner)
+    }
+
+    /// Indices of the winners, empty until the game is done.
+    fn winners(&self) -> Vec<usize> {
+        match &self.inner.state {
+            system::GameState::Done { winner_indices } => winner_indices.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The event log, one line per event.
+    fn events(&self) -> Vec<String> {
+        self.inner.events.iter().map(|e| e.to_string()).collect()
+    }
+}
+
+/// What a player sees, encoded as in `pawlice_academy::bears::env`.
+#[pyclass(get_all)]
+struct Observation {
+    player_index: usize,
+    features: Vec<f32>,
+    action_mask: Vec<bool>,
+}
+
+impl From<env::Observation> for Observation {
+    fn from(observation: env::Observation) -> Self {
+        Self {
+            player_index: observation.player_index,
+            features: observation.features,
+            action_mask: observation.action_mask,
+        }
+    }
+}
+
+/// The gym-style training environment. Seats listed in `opponents` are played by the built-in
+/// agents, every other seat through `step`.
+#[pyclass]
+struct Env {
+    inner: env::Env,
+}
+
+#[pymethods]
+impl Env {
+    #[new]
+    #[pyo3(signature = (num_players, opponents=None))]
+    fn new(num_players: usize, opponents: Option<Vec<(usize, String)>>) -> PyResult<Self> {
+        let mut inner = env::Env::new(GameRules::default(), num_players).ok_or_else(|| {
+            PyValueError::new_err(format!("Invalid number of players: {}", num_players))
+        })?;
+        for (player_index, strategy) in opponents.unwrap_or_default() {
+            inner
+                .opponents
+                .push((player_index, parse_strategy(&strategy)?));
+        }
+        Ok(Self { inner })
+    }
+
+    #[getter]
+    fn observation_size(&self) -> usize {
+        self.inner.observation_size()
+    }
+
+    #[getter]
+    fn action_count(&self) -> usize {
+        self.inner.action_count()
+    }
+
+    fn reset(&mut self, seed: u64) -> Observation {
+        self.inner.reset(seed).into()
+    }
+
+    /// Returns `(observation, reward, done, info)`, and raises `ValueError` for illegal actions.
+    fn step<'py>(
+        &mut self,
+        py: Python<'py>,
+        action_index: usize,
+    ) -> PyResult<(Observation, f32, bool, Bound<'py, PyDict>)> {
+        let step = self.inner.step(action_index).ok_or_else(|| {
+            PyValueError::new_err(format!("Illegal action index: {}", action_index))
+        })?;
+        let info = PyDict::new_bound(py);
+        info.set_item("player_index", step.info.player_index)?;
+        info.set_item("action", Action::from(step.info.action).into_py(py))?;
+        info.set_item("scores", step.info.scores)?;
+        info.set_item("winner_indices", step.info.winner_indices)?;
+        Ok((step.observation.into(), step.reward, step.done, info))
+    }
+}
+
+/// Plays `num_games` games of `num_players` seats with the same agent in every seat, game `g`
+/// dealt from `seed + g`, and returns the scores of every game by player index.
+#[pyfunction]
+#[pyo3(signature = (num_games, num_players, seed=0, strategy="random"))]
+fn simulate(
+    py: Python<'_>,
+    num_games: usize,
+    num_players: usize,
+    seed: u64,
+    strategy: &str,
+) -> PyResult<Vec<Vec<usize>>> {
+    let strategy = parse_strategy(strategy)?;
+    if !GameRules::default().allows(num_players) {
+        return Err(PyValueError::new_err(format!(
+            "Invalid number of players: {}",
+            num_players
+        )));
+    }
+    Ok(py.allow_threads(|| {
+        (0..num_games)
+            .map(|g| {
+                let mut table = system::GameTable::from_seed(
+                    num_players,
+                    GameRules::default(),
+                    system::InitialPlayerSelectionStrategy::Random,
+                    system::QuestTileSelectionStrategy::Random,
+                    seed.wrapping_add(g as u64),
+                )
+                .expect("The number of players was checked");
+                for player in table.players.iter_mut() {
+                    player.strategy = strategy.clone();
+                }
+                table.play_until_done();
+                scores(&table)
+            })
+            .collect()
+    }))
+}
+
+fn parse_strategy(name: &str) -> PyResult<system::ActionSelectionStrategy> {
+    match name {
+        "random" => Ok(system::ActionSelectionStrategy::Random),
+        "greedy" => Ok(system::ActionSelectionStrategy::Greedy),
+        other => Err(PyValueError::new_err(format!(
+            "Unknown strategy: {}",
+            other
+        ))),
+    }
+}
+
+fn scores(table: &system::GameTable) -> Vec<usize> {
+    let mut scores = vec![0; table.players.len()];
+    for player in &table.players {
+        scores[player.index] = player.score();
+    }
+    scores
+}
+
+#[pymodule]
+fn pawlice(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Action>()?;
+    module.add_class::<GameTable>()?;
+    module.add_class::<Observation>()?;
+    module.add_class::<Env>()?;
+    module.add_function(wrap_pyfunction!(simulate, module)?)?;
+    Ok(())
+}
@@ -0,0 +1,41 @@
+"""Smoke test for the bindings. Build them with `maturin develop` first, then run
+`python -m unittest discover python/tests`."""
+
+import unittest
+
+import pawlice
+
+
+class SmokeTest(unittest.TestCase):
+    def test_game_table_plays_until_done(self):
+        table = pawlice.GameTable(3, seed=1, strategy="greedy")
+        table.play_until_done()
+        self.assertTrue(table.done)
+        self.assertEqual(len(table.scores()), 3)
+        self.assertTrue(table.winners())
+
+    def test_actions_are_applied_by_hand(self):
+        table = pawlice.GameTable(2, seed=2)
+        self.assertIn(pawlice.Action.draw_resource(), table.legal_actions())
+        self.assertTrue(table.apply_action(pawlice.Action.draw_resource()))
+        self.assertFalse(table.apply_action(pawlice.Action.complete_open_quest(99)))
+
+    def test_env_steps_through_a_game(self):
+        env = pawlice.Env(2, opponents=[(1, "greedy")])
+        observation = env.reset(3)
+        self.assertEqual(len(observation.features), env.observation_size)
+        done = False
+        while not done:
+            action = observation.action_mask.index(True)
+            observation, reward, done, info = env.step(action)
+            self.assertGreaterEqual(reward, 0)
+        self.assertEqual(len(info["scores"]), 2)
+        with self.assertRaises(ValueError):
+            env.step(0)
+
+    def test_simulate_is_reproducible(self):
+        self.assertEqual(pawlice.simulate(5, 4, seed=9), pawlice.simulate(5, 4, seed=9))
+
+
+if __name__ == "__main__":
+    unittest.main()
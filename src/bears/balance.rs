@@ -0,0 +1,399 @@
+//! Balance reports: whether some bears or seats win more often than they should.
+
+use super::models::BearCard;
+use super::rules::GameRules;
+use super::system::{
+    ActionSelectionStrategy, GameState, GameTable, InitialPlayerSelectionStrategy,
+    QuestTileSelectionStrategy,
+};
+use std::fmt::Write;
+
+/// Significance level below which the report flags an imbalance.
+const ALPHA: f64 = 0.05;
+
+/// What games a balance report plays.
+#[derive(Debug, Clone)]
+pub struct BalanceSettings {
+    pub rules: GameRules,
+    pub num_players: usize,
+    pub num_games: usize,
+    /// Game `g` is dealt from `seed + g`.
+    pub seed: u64,
+    /// The agent that plays every seat.
+    pub strategy: ActionSelectionStrategy,
+    pub initial_player_selection_strategy: InitialPlayerSelectionStrategy,
+    /// Bears to seat in order instead of dealing them at random, to isolate bear effects from
+    /// seat effects.
+    pub fixed_bears: Option<Vec<BearCard>>,
+}
+
+/// Observed wins against the wins expected if every option were equally strong. Ties split the
+/// win between the tied players.
+#[derive(Debug, Clone)]
+pub struct WinRateTest {
+    /// What each option is, such as a bear or a position in turn order.
+    pub labels: Vec<String>,
+    /// Games each option was played in.
+    pub games: Vec<usize>,
+    pub observed: Vec<f64>,
+    pub expected: Vec<f64>,
+    pub chi_square: f64,
+    pub degrees_of_freedom: usize,
+    /// Probability of a chi-square at least this large if every option were equally strong.
+    pub p_value: f64,
+}
+
+/// Whether bears or turn order change the odds of winning.
+#[derive(Debug, Clone)]
+pub struct BalanceReport {
+    pub num_games: usize,
+    /// Bears have no abilities, so this only checks that no bear is dealt into better seats.
+    pub bears: WinRateTest,
+    /// Wins by position in turn order.
+    pub turn_order: WinRateTest,
+}
+
+/// Why a balance report cannot be run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BalanceError {
+    /// The rules are invalid or do not allow `num_players` players.
+    InvalidNumberOfPlayers { num_players: usize },
+    /// `fixed_bears` does not name one bear per player.
+    WrongNumberOfBears { num_players: usize, bears: usize },
+    /// `fixed_bears` names a bear twice, but every player at a table has a different bear.
+    DuplicateBear { bear: BearCard },
+    /// The rules seat a solo automaton, whose wins would count as a seat's.
+    AutomatonSeat,
+}
+
+/// Plays the games of `settings` and tests their win rates.
+pub fn run(settings: &BalanceSettings) -> Result<BalanceReport, BalanceError> {
+    let bears = BearCard::all();
+    let num_players = settings.num_players;
+    if settings.rules.validate().is_err() || !settings.rules.allows(num_players) {
+        return Err(BalanceError::InvalidNumberOfPlayers { num_players });
+    }
+    if settings.rules.seats(num_players) > num_players {
+        return Err(BalanceError::AutomatonSeat);
+    }
+    if let Some(fixed_bears) = &settings.fixed_bears {
+        if fixed_bears.len() != num_players {
+            return Err(BalanceError::WrongNumberOfBears {
+                num_players,
+                bears: fixed_bears.len(),
+            });
+        }
+        for (i, bear) in fixed_bears.iter().enumerate() {
+            if fixed_bears[..i].contains(bear) {
+                return Err(BalanceError::DuplicateBear { bear: *bear });
+            }
+        }
+    }
+    let mut bear_games = vec![0; bears.len()];
+    let mut bear_wins = vec![0.0; bears.len()];
+    let mut bear_expected = vec![0.0; bears.len()];
+    let mut turn_wins = vec![0.0; num_players];
+
+    for g in 0..settings.num_games {
+        let mut game_table = GameTable::from_seed(
+            num_players,
+            settings.rules.clone(),
+            settings.initial_player_selection_strategy.clone(),
+            QuestTileSelectionStrategy::Random,
+            settings.seed.wrapping_add(g as u64),
+        )
+        .expect("Balance reports are run with a valid number of players");
+        for (seat, player) in game_table.players.iter_mut().enumerate() {
+            player.strategy = settings.strategy.clone();
+            if let Some(fixed_bears) = &settings.fixed_bears {
+                player.bear = fixed_bears[seat];
+            }
+        }
+        game_table.play_until_done();
+
+        let winner_indices = match &game_table.state {
+            GameState::Done { winner_indices } => winner_indices.clone(),
+            _ => unreachable!("The game was played until done"),
+        };
+        // Players are kept in turn order once the initial player has been selected.
+        for (turn, player) in game_table.players.iter().enumerate() {
+            let bear = bears.iter().position(|b| *b == player.bear).unwrap();
+            bear_games[bear] += 1;
+            bear_expected[bear] += 1.0 / num_players as f64;
+            if winner_indices.contains(&player.index) {
+                let share = 1.0 / winner_indices.len() as f64;
+                bear_wins[bear] += share;
+                turn_wins[turn] += share;
+            }
+        }
+    }
+
+    let played: Vec<usize> = (0..bears.len()).filter(|&b| bear_games[b] > 0).collect();
+    Ok(BalanceReport {
+        num_games: settings.num_games,
+        bears: WinRateTest::new(
+            played.iter().map(|&b| bears[b].to_string()).collect(),
+            played.iter().map(|&b| bear_games[b]).collect(),
+            played.iter().map(|&b| bear_wins[b]).collect(),
+            played.iter().map(|&b| bear_expected[b]).collect(),
+        ),
+        turn_order: WinRateTest::new(
+            (1..=num_players).map(|t| format!("turn {}", t)).collect(),
+            vec![settings.num_games; num_players],
+            turn_wins,
+            vec![settings.num_games as f64 / num_players as f64; num_players],
+        ),
+    })
+}
+
+impl std::fmt::Display for BalanceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BalanceError::InvalidNumberOfPlayers { num_players } => {
+                write!(f, "Invalid number of players: {}", num_players)
+            }
+            BalanceError::WrongNumberOfBears { num_players, bears } => write!(
+                f,
+                "{} bears were given for {} players, who need one each",
+                bears, num_players
+            ),
+            BalanceError::DuplicateBear { bear } => {
+                write!(f, "The {} bear was given to more than one player", bear)
+            }
+            BalanceError::AutomatonSeat => {
+                write!(f, "Balance reports cannot seat the solo automaton")
+            }
+        }
+    }
+}
+
+impl WinRateTest {
+    fn new(labels: Vec<String>, games: Vec<usize>, observed: Vec<f64>, expected: Vec<f64>) -> Self {
+        let chi_square = observed
+            .iter()
+            .zip(&expected)
+            .filter(|(_, e)| **e > 0.0)
+            .map(|(o, e)| (o - e) * (o - e) / e)
+            .sum();
+        let degrees_of_freedom = labels.len().saturating_sub(1);
+        Self {
+            labels,
+            games,
+            observed,
+            expected,
+            chi_square,
+            degrees_of_freedom,
+            p_value: chi_square_p_value(chi_square, degrees_of_freedom),
+        }
+    }
+
+    /// Whether the win rates differ more than chance explains, at the 5% level.
+    pub fn is_significant(&self) -> bool {
+        self.p_value < ALPHA
+    }
+
+    fn write_to(&self, out: &mut String, title: &str) {
+        let width = self
+            .labels
+            .iter()
+            .map(|l| l.len())
+            .max()
+            .unwrap_or(0)
+            .max(5);
+        let _ = writeln!(out, "{}", title);
+        let _ = writeln!(
+            out,
+            "{:<width$}  {:>6}  {:>8}  {:>8}  {:>8}",
+            "", "games", "wins", "expected", "win rate"
+        );
+        for i in 0..self.labels.len() {
+            let _ = writeln!(
+                out,
+                "{:<width$}  {:>6}  {:>8.1}  {:>8.1}  {:>7.1}%",
+                self.labels[i],
+                self.games[i],
+                self.observed[i],
+                self.expected[i],
+                100.0 * self.observed[i] / self.games[i].max(1) as f64
+            );
+        }
+        let _ = writeln!(
+            out,
+            "chi-square = {:.2} with {} degrees of freedom, p = {:.4}: {}\n",
+            self.chi_square,
+            self.degrees_of_freedom,
+            self.p_value,
+            if self.is_significant() {
+                "win rates differ significantly"
+            } else {
+                "no significant difference"
+            }
+        );
+    }
+}
+
+impl BalanceReport {
+    /// Both tests as tables, for a terminal.
+    pub fn summary(&self) -> String {
+        let mut out = format!("Balance report over {} games\n\n", self.num_games);
+        self.bears.write_to(
+            &mut out,
+            "Is any bear overpowered?\n\
+             Bears have no abilities yet, so they all play alike: a significant difference\n\
+             points at how bears are dealt, not at a strong bear.",
+        );
+        self.turn_order
+            .write_to(&mut out, "Does turn order matter?");
+        out
+    }
+}
+
+/// Probability of a chi-square statistic at least as large as `x` under the null hypothesis.
+fn chi_square_p_value(x: f64, degrees_of_freedom: usize) -> f64 {
+    if degrees_of_freedom == 0 || x <= 0.0 {
+        return 1.0;
+    }
+    upper_regularized_gamma(degrees_of_freedom as f64 / 2.0, x / 2.0)
+}
+
+/// Q(a, x), using the series expansion below `a + 1` and a continued fraction above it.
+fn upper_regularized_gamma(a: f64, x: f64) -> f64 {
+    let log_prefix = a * x.ln() - x - ln_gamma(a);
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut n = a;
+        while term.abs() > sum.abs() * 1e-15 {
+            n += 1.0;
+            term *= x / n;
+            sum += term;
+        }
+        1.0 - sum * log_prefix.exp()
+    } else {
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        log_prefix.exp() * h
+    }
+}
+
+/// Lanczos approximation of ln Γ(x) for x > 0.
+fn ln_gamma(x: f64) -> f64 {
+    let coefficients = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.120_865_097_386_617_9e-2,
+        -0.539_523_938_495_3e-5,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let series = coefficients
+        .iter()
+        .enumerate()
+        .fold(1.000_000_000_190_015, |acc, (i, c)| {
+            acc + c / (x + 1.0 + i as f64)
+        });
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bears::rules::SoloOpponent;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "{} is not close to {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn ln_gamma_matches_factorials() {
+        assert_close(ln_gamma(1.0), 0.0);
+        assert_close(ln_gamma(5.0), 24f64.ln());
+        assert_close(ln_gamma(0.5), std::f64::consts::PI.sqrt().ln());
+    }
+
+    #[test]
+    fn upper_regularized_gamma_matches_closed_forms() {
+        // Q(1, x) = e^-x, on both sides of the switch between series and continued fraction.
+        for x in [0.1f64, 1.0, 2.5, 10.0] {
+            assert_close(upper_regularized_gamma(1.0, x), (-x).exp());
+        }
+        // Q(2, x) = (1 + x) e^-x.
+        for x in [0.5f64, 3.0, 8.0] {
+            assert_close(upper_regularized_gamma(2.0, x), (1.0 + x) * (-x).exp());
+        }
+    }
+
+    #[test]
+    fn chi_square_p_values_match_tables() {
+        assert_close(chi_square_p_value(3.841_459, 1), 0.05);
+        assert_close(chi_square_p_value(6.634_897, 1), 0.01);
+        assert_close(chi_square_p_value(5.991_465, 2), 0.05);
+        assert_close(chi_square_p_value(11.070_498, 5), 0.05);
+        assert_close(chi_square_p_value(4.351_460, 5), 0.5);
+        assert_eq!(chi_square_p_value(0.0, 3), 1.0);
+        assert_eq!(chi_square_p_value(7.0, 0), 1.0);
+    }
+
+    fn settings(num_players: usize, fixed_bears: Option<Vec<BearCard>>) -> BalanceSettings {
+        BalanceSettings {
+            rules: GameRules::default(),
+            num_players,
+            num_games: 4,
+            seed: 0,
+            strategy: ActionSelectionStrategy::Greedy,
+            initial_player_selection_strategy: InitialPlayerSelectionStrategy::First,
+            fixed_bears,
+        }
+    }
+
+    #[test]
+    fn rejects_tables_it_cannot_deal() {
+        use BearCard::{Panda, Polar, Sloth};
+        assert!(run(&settings(2, Some(vec![Polar, Panda]))).is_ok());
+        assert_eq!(
+            run(&settings(7, None)).err(),
+            Some(BalanceError::InvalidNumberOfPlayers { num_players: 7 })
+        );
+        assert_eq!(
+            run(&settings(3, Some(vec![Polar, Panda]))).err(),
+            Some(BalanceError::WrongNumberOfBears {
+                num_players: 3,
+                bears: 2
+            })
+        );
+        assert_eq!(
+            run(&settings(3, Some(vec![Polar, Sloth, Polar]))).err(),
+            Some(BalanceError::DuplicateBear { bear: Polar })
+        );
+        let mut solo = settings(1, None);
+        solo.rules.solo_opponent = SoloOpponent::Automaton;
+        assert_eq!(run(&solo).err(), Some(BalanceError::AutomatonSeat));
+    }
+}
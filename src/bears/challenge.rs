@@ -0,0 +1,201 @@
+//! Solo challenges that can be shared as a short code.
+
+use super::rules::{GameRules, SoloOpponent};
+use super::system::{
+    ActionSelectionStrategy, GameState, GameTable, InitialPlayerSelectionStrategy,
+    QuestTileSelectionStrategy,
+};
+use std::str::FromStr;
+
+/// A solo puzzle: one player, the deal from `seed`, `turns` turns to reach a reward of `target`.
+/// Its code, `seed-turns-target`, is all it takes for someone else to play the same challenge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Challenge {
+    pub seed: u64,
+    pub turns: usize,
+    pub target: usize,
+}
+
+/// How a challenge went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChallengeResult {
+    pub score: usize,
+    pub turns_played: usize,
+    /// 0 when the target was missed, and up to 3 for beating it by half again.
+    pub stars: usize,
+}
+
+impl Challenge {
+    /// The standard rules against this challenge's target and turn limit.
+    pub fn rules(&self) -> GameRules {
+        GameRules {
+            solo_opponent: SoloOpponent::ScoreTarget(self.target),
+            turn_limit: Some(self.turns),
+            ..GameRules::default()
+        }
+    }
+
+    /// The dealt table, ready for the player's first turn.
+    pub fn table(&self, strategy: ActionSelectionStrategy) -> GameTable {
+        let mut table = GameTable::from_seed(
+            1,
+            self.rules(),
+            InitialPlayerSelectionStrategy::First,
+            QuestTileSelectionStrategy::Random,
+            self.seed,
+        )
+        .expect("The standard rules allow solo games");
+        table.players[0].strategy = strategy;
+        table.play_one_step();
+        table
+    }
+
+    /// The outcome of a finished challenge table, or `None` while it is still being played.
+    pub fn result(&self, table: &GameTable) -> Option<ChallengeResult> {
+        match table.state {
+            GameState::Done { .. } => {
+                let score = table.players[0].score();
+                Some(ChallengeResult {
+                    score,
+                    turns_played: table.turns_played,
+                    stars: self.stars(score),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// One star for reaching the target, two for beating it by a quarter, three for half.
+    pub fn stars(&self, score: usize) -> usize {
+        [self.target, self.target * 5 / 4, self.target * 3 / 2]
+            .iter()
+            .filter(|&&threshold| score >= threshold)
+            .count()
+    }
+}
+
+impl std::fmt::Display for Challenge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}-{}", self.seed, self.turns, self.target)
+    }
+}
+
+impl FromStr for Challenge {
+    type Err = String;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid challenge code {}, expected seed-turns-target",
+                code
+            )
+        };
+        let parts: Vec<&str> = code.trim().split('-').collect();
+        let challenge = match parts[..] {
+            [seed, turns, target] => Self {
+                seed: seed.parse().map_err(|_| invalid())?,
+                turns: turns.parse().map_err(|_| invalid())?,
+                target: target.parse().map_err(|_| invalid())?,
+            },
+            _ => return Err(invalid()),
+        };
+        match challenge.turns {
+            0 => Err(format!(
+                "Invalid challenge code {}, it allows no turns",
+                code
+            )),
+            _ => Ok(challenge),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip() {
+        for challenge in [
+            Challenge {
+                seed: 0,
+                turns: 30,
+                target: 12,
+            },
+            Challenge {
+                seed: u64::MAX,
+                turns: 1,
+                target: 0,
+            },
+        ] {
+            assert_eq!(challenge.to_string().parse(), Ok(challenge));
+        }
+        assert_eq!(
+            " 42-30-12\n".parse(),
+            Ok(Challenge {
+                seed: 42,
+                turns: 30,
+                target: 12
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_codes() {
+        for code in [
+            "",
+            "42",
+            "42-30",
+            "42-30-12-1",
+            "42--12",
+            "a-30-12",
+            "-1-30-12",
+            "1-2-x",
+            "1-0-12",
+        ] {
+            assert!(code.parse::<Challenge>().is_err(), "{:?} parsed", code);
+        }
+    }
+
+    #[test]
+    fn replays_the_same_game_from_a_code() {
+        let challenge: Challenge = "7-20-10".parse().unwrap();
+        let play = || {
+            let mut table = challenge.table(ActionSelectionStrategy::Greedy);
+            table.play_until_done();
+            challenge.result(&table).unwrap()
+        };
+        let result = play();
+        assert_eq!(play(), result);
+        assert!(result.turns_played <= 20);
+        assert_eq!(result.stars, challenge.stars(result.score));
+    }
+
+    #[test]
+    fn a_challenge_without_turns_ends_before_the_first_one() {
+        let challenge = Challenge {
+            seed: 3,
+            turns: 0,
+            target: 12,
+        };
+        let table = challenge.table(ActionSelectionStrategy::Greedy);
+        let result = challenge
+            .result(&table)
+            .expect("The game is over after setup");
+        assert_eq!(result.turns_played, 0);
+        assert_eq!(result.score, 0);
+        assert_eq!(result.stars, 0);
+    }
+
+    #[test]
+    fn awards_stars_by_how_far_the_target_was_beaten() {
+        let challenge = Challenge {
+            seed: 0,
+            turns: 30,
+            target: 12,
+        };
+        assert_eq!(challenge.stars(11), 0);
+        assert_eq!(challenge.stars(12), 1);
+        assert_eq!(challenge.stars(15), 2);
+        assert_eq!(challenge.stars(18), 3);
+    }
+}
@@ -0,0 +1,348 @@
+//! A gym-style environment over `GameTable` for training agents.
+//!
+//! ```
+//! use pawlice_academy::bears::env::Env;
+//! use pawlice_academy::bears::rules::GameRules;
+//!
+//! let mut env = Env::new(GameRules::default(), 2).expect("2 players are allowed");
+//! let mut observation = env.reset(7);
+//! loop {
+//!     let action = observation.action_mask.iter().position(|&legal| legal).unwrap();
+//!     let step = env.step(action).expect("Masked actions are legal");
+//!     if step.done {
+//!         break;
+//!     }
+//!     observation = step.observation;
+//! }
+//! ```
+
+use super::models::{self, QuestCard, QuestId, RESOURCE_KINDS};
+use super::rules::GameRules;
+use super::system::{
+    Action, ActionSelectionStrategy, GameState, GameTable, InitialPlayerSelectionStrategy,
+    QuestTileSelectionStrategy,
+};
+use super::view::PlayerView;
+
+/// Features kept for every seat: whether it is taken, score, hand size and quest tiles.
+const PLAYER_FEATURES: usize = 4;
+/// Quests remaining, resources remaining, discard pile size, turns played and consecutive passes.
+const TABLE_FEATURES: usize = 5;
+
+/// Every seat is played through `step` unless it is listed in `opponents`, so leaving
+/// `opponents` empty gives self-play. A solo automaton starts out as an opponent.
+#[derive(Debug, Clone)]
+pub struct Env {
+    /// The rules every game of the environment is played by.
+    pub rules: GameRules,
+    pub num_players: usize,
+    /// Player indices played by a built-in agent instead of the caller.
+    pub opponents: Vec<(usize, ActionSelectionStrategy)>,
+    /// The game being played, or `None` before the first `reset`.
+    pub table: Option<GameTable>,
+}
+
+/// What the player to move sees, encoded for a model.
+#[derive(Debug, Clone)]
+pub struct Observation {
+    /// The player the observation is for.
+    pub player_index: usize,
+    /// `Env::observation_size` features, laid out as described on `encode`.
+    pub features: Vec<f32>,
+    /// `Env::action_count` flags, true for the legal action indices.
+    pub action_mask: Vec<bool>,
+}
+
+/// Details of a step that are not part of the reward.
+#[derive(Debug, Clone)]
+pub struct StepInfo {
+    /// The player who took the action.
+    pub player_index: usize,
+    /// The action that was played.
+    pub action: Action,
+    /// Scores by player index.
+    pub scores: Vec<usize>,
+    /// Empty until the game is done.
+    pub winner_indices: Vec<usize>,
+}
+
+/// The result of `Env::step`.
+#[derive(Debug, Clone)]
+pub struct Step {
+    /// The observation of the next player to move, or of the acting player once done.
+    pub observation: Observation,
+    /// Reward the acting player earned with the action.
+    pub reward: f32,
+    /// Whether the game is over, after which `reset` starts the next one.
+    pub done: bool,
+    pub info: StepInfo,
+}
+
+impl Env {
+    /// An environment for `num_players` players, or `None` if the rules do not allow them.
+    pub fn new(rules: GameRules, num_players: usize) -> Option<Self> {
+        if rules.validate().is_err() || !rules.allows(num_players) {
+            return None;
+        }
+        let opponents = match rules.seats(num_players) > num_players {
+            true => vec![(num_players, ActionSelectionStrategy::Greedy)],
+            false => Vec::new(),
+        };
+        Some(Self {
+            rules,
+            num_players,
+            opponents,
+            table: None,
+        })
+    }
+
+    /// Length of `Observation::features`.
+    pub fn observation_size(&self) -> usize {
+        let quests = QuestId::all().count();
+        RESOURCE_KINDS
+            + (self.rules.quest_tiles_per_player + self.rules.open_quests) * quests
+            + self.rules.seats(self.rules.max_players) * PLAYER_FEATURES
+            + TABLE_FEATURES
+    }
+
+    /// Number of action indices: drawing, one per quest tile, one per open quest, and passing.
+    pub fn action_count(&self) -> usize {
+        2 + self.rules.quest_tiles_per_player + self.rules.open_quests
+    }
+
+    /// Deals a new game from `seed` and plays the opponents until it is the caller's turn.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        let mut table = GameTable::from_seed(
+            self.num_players,
+            self.rules.clone(),
+            InitialPlayerSelectionStrategy::Random,
+            QuestTileSelectionStrategy::Random,
+            seed,
+        )
+        .expect("The environment was created with a valid number of players");
+        for (player_index, strategy) in &self.opponents {
+            if let Some(player) = table.players.iter_mut().find(|p| p.index == *player_index) {
+                player.strategy = strategy.clone();
+            }
+        }
+        table.play_one_step();
+        self.table = Some(table);
+        self.play_opponents();
+        self.observation()
+    }
+
+    /// Plays `action_index` for the player to move, then the opponents until it is the caller's
+    /// turn again. Returns `None`, leaving the game untouched, if the action is not legal or
+    /// the environment has not been reset.
+    pub fn step(&mut self, action_index: usize) -> Option<Step> {
+        let action = self.action(action_index)?;
+        let table = self.table.as_mut()?;
+        let player_index = table.current_player_index()?;
+        let before = table.player(player_index).score();
+        if !table.apply_action(action) {
+            return None;
+        }
+        let reward = (table.player(player_index).score() - before) as f32;
+        self.play_opponents();
+
+        let table = self.table.as_ref()?;
+        let mut scores = vec![0; table.players.len()];
+        for player in &table.players {
+            scores[player.index] = player.score();
+        }
+        let (done, winner_indices) = match &table.state {
+            GameState::Done { winner_indices } => (true, winner_indices.clone()),
+            _ => (false, Vec::new()),
+        };
+        Some(Step {
+            observation: match table.current_player_index() {
+                Some(_) => self.observation(),
+                None => self.observation_for(player_index),
+            },
+            reward,
+            done,
+            info: StepInfo {
+                player_index,
+                action,
+                scores,
+                winner_indices,
+            },
+        })
+    }
+
+    /// The observation of the player to move, or of the first player once the game is done.
+    pub fn observation(&self) -> Observation {
+        let player_index = self
+            .table
+            .as_ref()
+            .and_then(|t| t.current_player_index())
+            .unwrap_or(0);
+        self.observation_for(player_index)
+    }
+
+    /// What the player with `player_index` sees, whether or not it is their turn.
+    pub fn observation_for(&self, player_index: usize) -> Observation {
+        let view = self
+            .table
+            .as_ref()
+            .expect("Observations need a game, call reset first")
+            .view_for(player_index);
+        Observation {
+            player_index,
+            features: self.encode(&view),
+            action_mask: self.action_mask(&view),
+        }
+    }
+
+    /// Index of `action` in the action space.
+    pub fn action_index(&self, action: &Action) -> usize {
+        match *action {
+            Action::DrawResource => 0,
+            Action::CompleteQuestTile { tile_index } => 1 + tile_index,
+            Action::CompleteOpenQuest { quest_index } => {
+                1 + self.rules.quest_tiles_per_player + quest_index
+            }
+            Action::Pass => self.action_count() - 1,
+        }
+    }
+
+    /// The action at `index` of the action space, or `None` if there is no such index.
+    pub fn action(&self, index: usize) -> Option<Action> {
+        let tiles = self.rules.quest_tiles_per_player;
+        match index {
+            0 => Some(Action::DrawResource),
+            i if i <= tiles => Some(Action::CompleteQuestTile { tile_index: i - 1 }),
+            i if i <= tiles + self.rules.open_quests => Some(Action::CompleteOpenQuest {
+                quest_index: i - 1 - tiles,
+            }),
+            i if i == self.action_count() - 1 => Some(Action::Pass),
+            _ => None,
+        }
+    }
+
+    /// `action_count` flags, true for the actions legal in `view`.
+    pub fn action_mask(&self, view: &PlayerView) -> Vec<bool> {
+        let mut mask = vec![false; self.action_count()];
+        for action in &view.legal_actions {
+            mask[self.action_index(action)] = true;
+        }
+        mask
+    }
+
+    /// Encodes `view` as `observation_size` numbers: the count of each kind of card in hand,
+    /// a one-hot quest id per quest tile slot and then per open quest slot, the features of
+    /// every seat starting from the viewer in turn order, and the table counters.
+    pub fn encode(&self, view: &PlayerView) -> Vec<f32> {
+        let quests = QuestId::all().count();
+        let mut features = Vec::with_capacity(self.observation_size());
+
+        let mut hand = [0.0; RESOURCE_KINDS];
+        for card in &view.hand {
+            hand[card.kind_index()] += 1.0;
+        }
+        features.extend(hand);
+
+        let mut encode_quests = |slots: usize, cards: &mut dyn Iterator<Item = &QuestCard>| {
+            let start = features.len();
+            features.resize(start + slots * quests, 0.0);
+            for (slot, quest) in cards.take(slots).enumerate() {
+                features[start + slot * quests + quest.id.index()] = 1.0;
+            }
+        };
+        encode_quests(
+            self.rules.quest_tiles_per_player,
+            &mut view.quest_tiles.iter().map(|t| &t.quest),
+        );
+        encode_quests(self.rules.open_quests, &mut view.open_quests.iter());
+
+        let seats = self.rules.seats(self.rules.max_players);
+        let viewer = view
+            .players
+            .iter()
+            .position(|p| p.index == view.player_index)
+            .unwrap_or(0);
+        for offset in 0..seats {
+            match view.players.get((viewer + offset) % view.players.len()) {
+                Some(player) if offset < view.players.len() => features.extend([
+                    1.0,
+                    models::score(&player.completed_quests) as f32,
+                    player.hand_size as f32,
+                    player.quest_tile_count as f32,
+                ]),
+                _ => features.extend([0.0; PLAYER_FEATURES]),
+            }
+        }
+
+        features.extend([
+            view.quests_remaining as f32,
+            view.resources_remaining as f32,
+            view.discard_pile.len() as f32,
+            view.turns_played as f32,
+            view.consecutive_passes as f32,
+        ]);
+        debug_assert_eq!(features.len(), self.observation_size());
+        features
+    }
+
+    fn play_opponents(&mut self) {
+        let Some(table) = self.table.as_mut() else {
+            return;
+        };
+        while let Some(player_index) = table.current_player_index() {
+            if !self
+                .opponents
+                .iter()
+                .any(|(index, _)| *index == player_index)
+            {
+                break;
+            }
+            table.play_one_step();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_indices_round_trip() {
+        let env = Env::new(GameRules::default(), 3).unwrap();
+        let actions: Vec<Action> = (0..env.action_count())
+            .map(|index| env.action(index).expect("Every index has an action"))
+            .collect();
+        for (index, action) in actions.iter().enumerate() {
+            assert_eq!(env.action_index(action), index);
+        }
+        assert_eq!(actions[0], Action::DrawResource);
+        assert_eq!(actions[env.action_count() - 1], Action::Pass);
+        assert_eq!(env.action(env.action_count()), None);
+    }
+
+    #[test]
+    fn mask_matches_the_legal_actions() {
+        let mut env = Env::new(GameRules::default(), 2).unwrap();
+        let mut observation = env.reset(3);
+        for _ in 0..20 {
+            let table = env.table.as_ref().unwrap();
+            let legal = table.legal_actions();
+            for (index, &allowed) in observation.action_mask.iter().enumerate() {
+                let action = env.action(index).unwrap();
+                assert_eq!(allowed, legal.contains(&action), "{:?}", action);
+            }
+            assert_eq!(observation.features.len(), env.observation_size());
+
+            let illegal = observation.action_mask.iter().position(|&legal| !legal);
+            if let Some(index) = illegal {
+                assert!(env.step(index).is_none());
+            }
+            let index = observation.action_mask.iter().position(|&l| l).unwrap();
+            let step = env.step(index).unwrap();
+            assert_eq!(step.info.action, env.action(index).unwrap());
+            if step.done {
+                break;
+            }
+            observation = step.observation;
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! A recorded game as a single self-contained HTML page: the final scores, charts of every
+//! player's score and hand size over the game, and a turn by turn timeline of the events with
+//! each player's score and hand after them. The page loads nothing from anywhere, so it can be
+//! attached to a review or opened from disk.
+
+use super::render::Renderer;
+use super::spectator::Recording;
+use super::system::LoggedEvents;
+use super::view::CasterPlayer;
+use std::fmt::Write;
+
+/// Line colors of the charts, one per seat.
+const PALETTE: [&str; 7] = [
+    "#1f77b4", "#d62728", "#2ca02c", "#9467bd", "#ff7f0e", "#17becf", "#8c564b",
+];
+const CHART_WIDTH: f64 = 720.0;
+const CHART_HEIGHT: f64 = 260.0;
+const CHART_MARGIN: f64 = 40.0;
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; margin: 2em auto; max-width: 1100px; color: #222; }
+h1, h2 { font-weight: 600; }
+table { border-collapse: collapse; width: 100%; font-size: 0.9em; }
+th, td { border-bottom: 1px solid #ddd; padding: 4px 8px; text-align: left; vertical-align: top; }
+tr.completed { background: #f3f9f1; }
+.score { font-weight: 600; }
+.muted { color: #888; }
+.legend span { margin-right: 1.5em; }
+svg { display: block; margin: 1em 0; }
+svg text { font-size: 11px; fill: #555; }
+";
+
+/// The page for `recording`, or a page saying it is empty.
+pub fn export(recording: &Recording) -> String {
+    let mut players: Vec<&CasterPlayer> = match recording.frames.last() {
+        Some(frame) => frame.table.players.iter().collect(),
+        None => Vec::new(),
+    };
+    players.sort_by_key(|p| p.index);
+    let renderer = Renderer::plain().with_bears(players.iter().map(|p| (p.index, p.bear)));
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Pawlice Academy replay</title>\n<style>");
+    html.push_str(STYLE);
+    html.push_str("</style>\n</head>\n<body>\n<h1>Pawlice Academy replay</h1>\n");
+    let Some(last) = recording.frames.last() else {
+        html.push_str("<p>The recording is empty.</p>\n</body>\n</html>\n");
+        return html;
+    };
+
+    let _ = writeln!(
+        html,
+        "<p>{} players, {} turns. {}.</p>",
+        players.len(),
+        last.table.turns_played,
+        escape(&renderer.state_of(&last.table.state, last.table.turns_played))
+    );
+    html.push_str("<table>\n<tr><th>Player</th><th>Score</th><th>Completed quests</th></tr>\n");
+    for player in &players {
+        let completed: Vec<String> = player
+            .completed_quests
+            .iter()
+            .map(|q| format!("{} ({})", q.name(), q.reward()))
+            .collect();
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"score\">{}</td><td>{}</td></tr>",
+            swatch(player.index, &renderer.name(player.index)),
+            player.score(),
+            escape(&completed.join(", "))
+        );
+    }
+    html.push_str("</table>\n");
+
+    let turns: Vec<usize> = recording
+        .frames
+        .iter()
+        .map(|f| f.table.turns_played)
+        .collect();
+    let series = |value: fn(&CasterPlayer) -> usize| -> Vec<(usize, Vec<usize>)> {
+        players
+            .iter()
+            .map(|player| {
+                let values = recording
+                    .frames
+                    .iter()
+                    .map(|frame| {
+                        frame
+                            .table
+                            .players
+                            .iter()
+                            .find(|p| p.index == player.index)
+                            .map_or(0, value)
+                    })
+                    .collect();
+                (player.index, values)
+            })
+            .collect()
+    };
+    html.push_str("<h2>Score</h2>\n");
+    html.push_str(&chart(&turns, &series(CasterPlayer::score)));
+    html.push_str("<h2>Hand size</h2>\n");
+    html.push_str(&chart(&turns, &series(|p| p.hand.len())));
+    html.push_str("<p class=\"legend\">");
+    for player in &players {
+        html.push_str(&swatch(player.index, &renderer.name(player.index)));
+    }
+    html.push_str("</p>\n");
+
+    html.push_str("<h2>Timeline</h2>\n<table>\n<tr><th>Turn</th><th>Events</th>");
+    for player in &players {
+        let _ = write!(html, "<th>{}</th>", escape(&renderer.name(player.index)));
+    }
+    html.push_str("<th>Open quests</th></tr>\n");
+    for frame in &recording.frames {
+        let completed = frame
+            .events
+            .iter()
+            .any(|e| matches!(e, LoggedEvents::CompletedQuest { .. }));
+        let events: Vec<String> = frame
+            .events
+            .iter()
+            .map(|e| escape(&renderer.event(e)))
+            .collect();
+        let _ = write!(
+            html,
+            "<tr class=\"turn{}\"><td>{}</td><td>{}</td>",
+            if completed { " completed" } else { "" },
+            frame.table.turns_played,
+            events.join("<br>")
+        );
+        for player in &players {
+            match frame.table.players.iter().find(|p| p.index == player.index) {
+                Some(seat) => {
+                    let _ = write!(
+                        html,
+                        "<td><span class=\"score\">{}</span> <span class=\"muted\">·</span> {}</td>",
+                        seat.score(),
+                        escape(&renderer.cards(&seat.hand))
+                    );
+                }
+                None => html.push_str("<td></td>"),
+            }
+        }
+        let open: Vec<String> = frame
+            .table
+            .open_quests
+            .iter()
+            .map(|q| escape(&renderer.quest(q)))
+            .collect();
+        let _ = writeln!(html, "<td>{}</td></tr>", open.join("<br>"));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+/// An SVG line chart with one line per `(player_index, values)` over `turns`.
+fn chart(turns: &[usize], series: &[(usize, Vec<usize>)]) -> String {
+    let max_turn = turns.iter().copied().max().unwrap_or(0).max(1) as f64;
+    let max_value = series
+        .iter()
+        .flat_map(|(_, values)| values.iter().copied())
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    let x =
+        |turn: usize| CHART_MARGIN + turn as f64 / max_turn * (CHART_WIDTH - 2.0 * CHART_MARGIN);
+    let y = |value: f64| {
+        CHART_HEIGHT - CHART_MARGIN - value / max_value * (CHART_HEIGHT - 2.0 * CHART_MARGIN)
+    };
+
+    let mut svg = format!(
+        "<svg width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" role=\"img\">\n",
+        CHART_WIDTH, CHART_HEIGHT
+    );
+    let step = (max_value / 5.0).ceil().max(1.0);
+    let mut tick = 0.0;
+    while tick <= max_value {
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{0}\" x2=\"{1}\" y1=\"{2:.1}\" y2=\"{2:.1}\" stroke=\"#eee\"/>\
+             <text x=\"{3}\" y=\"{4:.1}\" text-anchor=\"end\">{5}</text>",
+            CHART_MARGIN,
+            CHART_WIDTH - CHART_MARGIN,
+            y(tick),
+            CHART_MARGIN - 6.0,
+            y(tick) + 4.0,
+            tick
+        );
+        tick += step;
+    }
+    let _ = writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">turn {}</text>",
+        CHART_WIDTH - CHART_MARGIN,
+        CHART_HEIGHT - CHART_MARGIN + 16.0,
+        max_turn
+    );
+    for (player_index, values) in series {
+        let points: Vec<String> = turns
+            .iter()
+            .zip(values)
+            .map(|(&turn, &value)| format!("{:.1},{:.1}", x(turn), y(value as f64)))
+            .collect();
+        let _ = writeln!(
+            svg,
+            "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"2\" points=\"{}\"/>",
+            color(*player_index),
+            points.join(" ")
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// `name` after a dot in the player's chart color.
+fn swatch(player_index: usize, name: &str) -> String {
+    format!(
+        "<span><span style=\"color: {}\">●</span> {}</span>",
+        color(player_index),
+        escape(name)
+    )
+}
+
+fn color(player_index: usize) -> &'static str {
+    PALETTE[player_index % PALETTE.len()]
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,197 @@
+//! A Monte Carlo Tree Search agent that only looks at what its player can see.
+
+use super::system::{greedy_choice, Action, GameState, GameTable};
+use super::view::PlayerView;
+use rand::{seq::SliceRandom, Rng};
+use std::time::{Duration, Instant};
+
+/// How long a search may take per move.
+#[derive(Debug, Clone)]
+pub enum SearchBudget {
+    /// Runs this many simulations.
+    Iterations(usize),
+    /// Simulates until this much time has passed.
+    Time(Duration),
+}
+
+const EXPLORATION: f64 = 0.7;
+
+struct Node {
+    action: Option<Action>,
+    mover: usize,
+    visits: f64,
+    availability: f64,
+    reward: f64,
+    children: Vec<usize>,
+}
+
+/// Picks an action for the viewer with information set Monte Carlo Tree Search. Every iteration
+/// plays on a fresh determinization of the view, so the search only relies on what the viewer
+/// knows.
+pub fn search(view: &PlayerView, budget: &SearchBudget, rng: &mut impl Rng) -> Action {
+    let legal_actions = &view.legal_actions;
+    if legal_actions.len() < 2 {
+        return legal_actions.first().copied().unwrap_or(Action::Pass);
+    }
+    let observer = view.player_index;
+    let mut nodes = vec![Node {
+        action: None,
+        mover: observer,
+        visits: 0.0,
+        availability: 0.0,
+        reward: 0.0,
+        children: Vec::new(),
+    }];
+    let started = Instant::now();
+    let mut iterations = 0;
+    while match budget {
+        SearchBudget::Iterations(n) => iterations < *n,
+        SearchBudget::Time(limit) => started.elapsed() < *limit,
+    } {
+        iterations += 1;
+        let mut game = view.determinize(rng);
+        let mut path = vec![0];
+        let mut node = 0;
+
+        // Selection and expansion.
+        while let Some(mover) = game.current_player_index() {
+            let legal = game.legal_actions();
+            let untried: Vec<Action> = legal
+                .iter()
+                .copied()
+                .filter(|a| {
+                    !nodes[node]
+                        .children
+                        .iter()
+                        .any(|&c| nodes[c].action == Some(*a))
+                })
+                .collect();
+            if let Some(&action) = untried.choose(rng) {
+                nodes.push(Node {
+                    action: Some(action),
+                    mover,
+                    visits: 0.0,
+                    availability: 1.0,
+                    reward: 0.0,
+                    children: Vec::new(),
+                });
+                let child = nodes.len() - 1;
+                nodes[node].children.push(child);
+                game.apply_action(action);
+                path.push(child);
+                break;
+            }
+            let available: Vec<usize> = nodes[node]
+                .children
+                .iter()
+                .copied()
+                .filter(|&c| legal.contains(&nodes[c].action.unwrap()))
+                .collect();
+            for &c in &available {
+                nodes[c].availability += 1.0;
+            }
+            let child = *available
+                .iter()
+                .max_by(|&&a, &&b| ucb(&nodes[a]).total_cmp(&ucb(&nodes[b])))
+                .expect("Every legal action has been tried");
+            game.apply_action(nodes[child].action.unwrap());
+            path.push(child);
+            node = child;
+        }
+
+        // Simulation.
+        rollout(&mut game, rng);
+        let winner_indices = match &game.state {
+            GameState::Done { winner_indices } => winner_indices.clone(),
+            _ => Vec::new(),
+        };
+
+        // Backpropagation, from the point of view of whoever chose each action.
+        for &n in &path {
+            nodes[n].visits += 1.0;
+            if winner_indices.contains(&nodes[n].mover) {
+                nodes[n].reward += 1.0 / winner_indices.len() as f64;
+            }
+        }
+    }
+
+    nodes[0]
+        .children
+        .iter()
+        .max_by(|&&a, &&b| nodes[a].visits.total_cmp(&nodes[b].visits))
+        .and_then(|&c| nodes[c].action)
+        .unwrap_or(legal_actions[0])
+}
+
+fn ucb(node: &Node) -> f64 {
+    if node.visits == 0.0 {
+        return f64::INFINITY;
+    }
+    node.reward / node.visits + EXPLORATION * (node.availability.ln() / node.visits).sqrt()
+}
+
+/// Plays `game` to the end. Quests are completed as soon as they are affordable, which makes
+/// rollouts both shorter and closer to how a sensible player behaves than uniform random play.
+fn rollout(game: &mut GameTable, rng: &mut impl Rng) {
+    while game.current_player_index().is_some() {
+        let legal_actions = game.legal_actions();
+        let action = if rng.gen_bool(0.9) {
+            let player = game.player(game.current_player_index().unwrap());
+            greedy_choice(&player.quest_tiles, &game.open_quests, &legal_actions)
+        } else {
+            *legal_actions.choose(rng).unwrap()
+        };
+        game.apply_action(action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bears::rules::GameRules;
+    use crate::bears::system::{InitialPlayerSelectionStrategy, QuestTileSelectionStrategy};
+    use crate::utils::Deck;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn completes_the_last_quest_to_win() {
+        let mut table = GameTable::from_seed(
+            2,
+            GameRules::default(),
+            InitialPlayerSelectionStrategy::First,
+            QuestTileSelectionStrategy::Random,
+            11,
+        )
+        .unwrap();
+        table.play_one_step();
+        // Leave a single open quest, give the current player exactly its cost and the opponent
+        // nothing, so completing it ends the game with a win while drawing risks a tie or loss.
+        let quest = table.open_quests[0];
+        table.open_quests = vec![quest];
+        table.quests = Deck::new();
+        for player in &mut table.players {
+            player.quest_tiles.clear();
+            for card in player.hand.drain(..) {
+                table.discard_pile.place(card);
+            }
+        }
+        for requirement in quest.cost() {
+            let card = table
+                .resources
+                .draw_first(|card| card.satisfies(requirement))
+                .unwrap();
+            table.players[0].hand.push(card);
+        }
+        table.check_card_conservation().unwrap();
+
+        let view = table.view_for(table.current_player_index().unwrap());
+        assert!(view.legal_actions.contains(&Action::DrawResource));
+        let action = search(
+            &view,
+            &SearchBudget::Iterations(200),
+            &mut StdRng::seed_from_u64(1),
+        );
+        assert_eq!(action, Action::CompleteOpenQuest { quest_index: 0 });
+    }
+}
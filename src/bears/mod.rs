@@ -1,2 +1,16 @@
+//! Pawlice Academy itself: the cards, the table, and the agents and tools that play it.
+
+pub mod balance;
+pub mod challenge;
+pub mod env;
+pub mod html;
+pub mod mcts;
 pub mod models;
+pub mod odds;
+pub mod render;
+pub mod rules;
+pub mod server;
+pub mod spectator;
 pub mod system;
+pub mod tournament;
+pub mod view;
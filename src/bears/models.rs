@@ -1,6 +1,13 @@
+//! The cards of the game: resources, bears and quests, and the quest registry.
+
 use enum_display::EnumDisplay;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, Copy, EnumDisplay)]
+/// What an ingredient card shows. `Any` only appears in quest costs.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, EnumDisplay, Serialize, Deserialize,
+)]
 pub enum IngredientKind {
     Fish,
     Berries,
@@ -10,7 +17,10 @@ pub enum IngredientKind {
     Any,
 }
 
-#[derive(Debug, Clone, Copy, EnumDisplay)]
+/// What a material card shows. `Any` only appears in quest costs.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, EnumDisplay, Serialize, Deserialize,
+)]
 pub enum MaterialKind {
     Snowball,
     Cotton,
@@ -22,7 +32,10 @@ pub enum MaterialKind {
     Any,
 }
 
-#[derive(Debug, Clone, Copy, EnumDisplay)]
+/// What a fortune card shows. Fortunes are never part of a quest cost.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, EnumDisplay, Serialize, Deserialize,
+)]
 pub enum FortuneKind {
     BeeAttack,
     Picnic,
@@ -34,14 +47,20 @@ pub enum FortuneKind {
     Famine,
 }
 
-#[derive(Debug, Clone, Copy, EnumDisplay)]
+/// A card of the resource deck, or one entry of a quest cost.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, EnumDisplay, Serialize, Deserialize,
+)]
 pub enum ResourceCard {
     Ingredient(IngredientKind),
     Material(MaterialKind),
     Fortune(FortuneKind),
 }
 
-#[derive(Debug, Clone, Copy, EnumDisplay)]
+/// The bear a player plays as. Every player at a table has a different bear.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, EnumDisplay, Serialize, Deserialize,
+)]
 pub enum BearCard {
     Polar,
     Panda,
@@ -51,245 +70,708 @@ pub enum BearCard {
     Sun,
 }
 
-#[derive(Debug, Clone)]
-pub struct QuestCard {
-    pub name: String,
-    pub cost: Vec<ResourceCard>,
+impl BearCard {
+    /// Every bear, which is also the most players a table can seat.
+    pub fn all() -> [BearCard; 6] {
+        [
+            BearCard::Polar,
+            BearCard::Panda,
+            BearCard::Sloth,
+            BearCard::Spectacled,
+            BearCard::Grizzly,
+            BearCard::Sun,
+        ]
+    }
+}
+
+/// Identifies a quest in the quest registry. Every `QuestId` names a quest that exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub struct QuestId(u8);
+
+/// The static description of a quest, shared by every card and tile of that quest.
+#[derive(Debug)]
+pub struct QuestData {
+    pub name: &'static str,
+    /// Resource cards paid to complete the quest, where `Any` entries accept any card of their
+    /// category.
+    pub cost: &'static [ResourceCard],
+    /// Points scored for completing the quest.
     pub reward: usize,
 }
 
-#[derive(Debug, Clone)]
+/// A quest of the quest deck, which anyone may complete once it is revealed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct QuestCard {
+    pub id: QuestId,
+}
+
+/// A quest dealt face down to a single player, which only they may complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct QuestTile {
     pub quest: QuestCard,
 }
 
-pub fn build_all_quests() -> (Vec<QuestCard>, Vec<QuestTile>) {
-    let quests = vec![
-        QuestCard {
-            name: "Tuna Sandwich".to_string(),
-            cost: vec![
-                ResourceCard::Ingredient(IngredientKind::Fish),
-                ResourceCard::Ingredient(IngredientKind::Wheat),
-                ResourceCard::Ingredient(IngredientKind::Any),
-            ],
-            reward: 3,
-        },
-        QuestCard {
-            name: "Treehouse".to_string(),
-            cost: vec![
-                ResourceCard::Material(MaterialKind::Bamboo),
-                ResourceCard::Material(MaterialKind::Cotton),
-                ResourceCard::Material(MaterialKind::Leaf),
-                ResourceCard::Material(MaterialKind::Any),
-            ],
-            reward: 4,
-        },
-        QuestCard {
-            name: "Tiny Home".to_string(),
-            cost: vec![
-                ResourceCard::Material(MaterialKind::Bamboo),
-                ResourceCard::Material(MaterialKind::Silk),
-                ResourceCard::Material(MaterialKind::Pebble),
-                ResourceCard::Material(MaterialKind::Any),
-            ],
-            reward: 4,
-        },
-        QuestCard {
-            name: "Tent".to_string(),
-            cost: vec![
-                ResourceCard::Material(MaterialKind::Bamboo),
-                ResourceCard::Material(MaterialKind::Cotton),
-                ResourceCard::Material(MaterialKind::Leaf),
-                ResourceCard::Material(MaterialKind::Any),
-            ],
-            reward: 4,
-        },
-        QuestCard {
-            name: "Tea".to_string(),
-            cost: vec![
-                ResourceCard::Ingredient(IngredientKind::Raindrop),
-                ResourceCard::Ingredient(IngredientKind::Raindrop),
-                ResourceCard::Material(MaterialKind::Leaf),
-                ResourceCard::Ingredient(IngredientKind::Any),
-            ],
-            reward: 4,
-        },
-        QuestCard {
-            name: "Sweater".to_string(),
-            cost: vec![
-                ResourceCard::Material(MaterialKind::Cotton),
-                ResourceCard::Material(MaterialKind::Silk),
-                ResourceCard::Material(MaterialKind::Any),
-                ResourceCard::Material(MaterialKind::Any),
-            ],
-            reward: 3,
-        },
-        QuestCard {
-            name: "Sushi".to_string(),
-            cost: vec![
-                ResourceCard::Ingredient(IngredientKind::Fish),
-                ResourceCard::Ingredient(IngredientKind::Rice),
-                ResourceCard::Ingredient(IngredientKind::Any),
-            ],
-            reward: 3,
-        },
-        QuestCard {
-            name: "Soup".to_string(),
-            cost: vec![
-                ResourceCard::Ingredient(IngredientKind::Raindrop),
-                ResourceCard::Ingredient(IngredientKind::Any),
-                ResourceCard::Ingredient(IngredientKind::Any),
-            ],
-            reward: 2,
-        },
-        QuestCard {
-            name: "Snow Cone".to_string(),
-            cost: vec![
-                ResourceCard::Material(MaterialKind::Snowball),
-                ResourceCard::Material(MaterialKind::Snowball),
-                ResourceCard::Ingredient(IngredientKind::Berries),
-                ResourceCard::Ingredient(IngredientKind::Any),
-            ],
-            reward: 4,
-        },
-        QuestCard {
-            name: "Ramen".to_string(),
-            cost: vec![
-                ResourceCard::Ingredient(IngredientKind::Raindrop),
-                ResourceCard::Ingredient(IngredientKind::Wheat),
-                ResourceCard::Ingredient(IngredientKind::Any),
-            ],
-            reward: 3,
-        },
-        QuestCard {
-            name: "Map".to_string(),
-            cost: vec![
-                ResourceCard::Ingredient(IngredientKind::Rice),
-                ResourceCard::Material(MaterialKind::Leaf),
-                ResourceCard::Material(MaterialKind::Pebble),
-                ResourceCard::Material(MaterialKind::Any),
-            ],
-            reward: 4,
-        },
-        QuestCard {
-            name: "Lean-To".to_string(),
-            cost: vec![
-                ResourceCard::Material(MaterialKind::Bamboo),
-                ResourceCard::Material(MaterialKind::Rope),
-                ResourceCard::Material(MaterialKind::Pebble),
-                ResourceCard::Material(MaterialKind::Any),
-            ],
-            reward: 4,
-        },
-        QuestCard {
-            name: "Igloo".to_string(),
-            cost: vec![
-                ResourceCard::Material(MaterialKind::Snowball),
-                ResourceCard::Material(MaterialKind::Snowball),
-                ResourceCard::Material(MaterialKind::Cotton),
-                ResourceCard::Material(MaterialKind::Any),
-            ],
-            reward: 4,
-        },
-        QuestCard {
-            name: "House Boat".to_string(),
-            cost: vec![
-                ResourceCard::Material(MaterialKind::Silk),
-                ResourceCard::Material(MaterialKind::Bamboo),
-                ResourceCard::Material(MaterialKind::Leaf),
-                ResourceCard::Material(MaterialKind::Any),
-                ResourceCard::Material(MaterialKind::Any),
-            ],
-            reward: 5,
-        },
-        QuestCard {
-            name: "Hair Brush".to_string(),
-            cost: vec![
-                ResourceCard::Material(MaterialKind::Rope),
-                ResourceCard::Material(MaterialKind::Bamboo),
-                ResourceCard::Material(MaterialKind::Any),
-                ResourceCard::Material(MaterialKind::Any),
-            ],
-            reward: 3,
-        },
-        QuestCard {
-            name: "Fruit Salad".to_string(),
-            cost: vec![
-                ResourceCard::Ingredient(IngredientKind::Berries),
-                ResourceCard::Ingredient(IngredientKind::Berries),
-                ResourceCard::Ingredient(IngredientKind::Any),
-            ],
-            reward: 3,
-        },
-        QuestCard {
-            name: "Fishing Rod".to_string(),
-            cost: vec![
-                ResourceCard::Material(MaterialKind::Rope),
-                ResourceCard::Material(MaterialKind::Bamboo),
-                ResourceCard::Ingredient(IngredientKind::Any),
-                ResourceCard::Ingredient(IngredientKind::Any),
-            ],
-            reward: 3,
-        },
-        QuestCard {
-            name: "Curry".to_string(),
-            cost: vec![
-                ResourceCard::Ingredient(IngredientKind::Rice),
-                ResourceCard::Ingredient(IngredientKind::Any),
-                ResourceCard::Ingredient(IngredientKind::Any),
-            ],
-            reward: 2,
-        },
-        QuestCard {
-            name: "Cave Hotel".to_string(),
-            cost: vec![
-                ResourceCard::Material(MaterialKind::Pebble),
-                ResourceCard::Material(MaterialKind::Silk),
-                ResourceCard::Material(MaterialKind::Leaf),
-                ResourceCard::Material(MaterialKind::Any),
-                ResourceCard::Material(MaterialKind::Any),
-            ],
-            reward: 5,
-        },
-        QuestCard {
-            name: "Castle".to_string(),
-            cost: vec![
-                ResourceCard::Material(MaterialKind::Pebble),
-                ResourceCard::Material(MaterialKind::Silk),
-                ResourceCard::Material(MaterialKind::Rope),
-                ResourceCard::Material(MaterialKind::Any),
-            ],
-            reward: 4,
-        },
-        QuestCard {
-            name: "Beary Pie".to_string(),
-            cost: vec![
-                ResourceCard::Ingredient(IngredientKind::Berries),
-                ResourceCard::Ingredient(IngredientKind::Wheat),
-                ResourceCard::Ingredient(IngredientKind::Any),
-            ],
-            reward: 3,
-        },
-        QuestCard {
-            name: "Bao".to_string(),
-            cost: vec![
-                ResourceCard::Ingredient(IngredientKind::Wheat),
-                ResourceCard::Ingredient(IngredientKind::Any),
-                ResourceCard::Ingredient(IngredientKind::Any),
-            ],
-            reward: 2,
-        },
-    ];
-
-    let quest_tiles = quests
-        .iter()
-        .cloned()
-        .map(|q| QuestTile { quest: q })
-        .collect();
+/// Number of distinct resource cards, counting the two `Any` wildcards.
+pub const RESOURCE_KINDS: usize = 22;
+
+/// A hand or quest cost stored as one count per kind of resource card, for simulations that
+/// check affordability millions of times. Adding, removing and counting are O(1), and matching
+/// a cost is O(`RESOURCE_KINDS`) whatever the size of the hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompactHand {
+    counts: [u8; RESOURCE_KINDS],
+    ingredients: u8,
+    materials: u8,
+}
+
+/// A multiset of resource cards, such as a quest cost or a hand to check against one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ResourceCounts {
+    counts: BTreeMap<ResourceCard, usize>,
+}
+
+impl IngredientKind {
+    /// Every ingredient a card can show, which excludes `Any`.
+    pub fn all() -> [IngredientKind; 5] {
+        [
+            IngredientKind::Fish,
+            IngredientKind::Berries,
+            IngredientKind::Wheat,
+            IngredientKind::Rice,
+            IngredientKind::Raindrop,
+        ]
+    }
+}
+
+impl MaterialKind {
+    /// Every material a card can show, which excludes `Any`.
+    pub fn all() -> [MaterialKind; 7] {
+        [
+            MaterialKind::Snowball,
+            MaterialKind::Cotton,
+            MaterialKind::Bamboo,
+            MaterialKind::Rope,
+            MaterialKind::Leaf,
+            MaterialKind::Silk,
+            MaterialKind::Pebble,
+        ]
+    }
+}
+
+impl FortuneKind {
+    /// Every fortune in the game.
+    pub fn all() -> [FortuneKind; 8] {
+        [
+            FortuneKind::BeeAttack,
+            FortuneKind::Picnic,
+            FortuneKind::Avalanche,
+            FortuneKind::Wildfire,
+            FortuneKind::Hibernation,
+            FortuneKind::BearHug,
+            FortuneKind::Bearglar,
+            FortuneKind::Famine,
+        ]
+    }
+}
+
+impl ResourceCard {
+    /// Every distinct card in the resource deck, which excludes the `Any` wildcards.
+    pub fn all() -> Vec<ResourceCard> {
+        IngredientKind::all()
+            .into_iter()
+            .map(ResourceCard::Ingredient)
+            .chain(MaterialKind::all().into_iter().map(ResourceCard::Material))
+            .chain(FortuneKind::all().into_iter().map(ResourceCard::Fortune))
+            .collect()
+    }
+
+    /// Position of this card in a `CompactHand`: ingredients, then materials, then fortunes,
+    /// each category followed by its `Any` wildcard.
+    pub fn kind_index(&self) -> usize {
+        match self {
+            ResourceCard::Ingredient(kind) => *kind as usize,
+            ResourceCard::Material(kind) => 6 + *kind as usize,
+            ResourceCard::Fortune(kind) => 14 + *kind as usize,
+        }
+    }
+
+    /// Whether this is a wildcard cost entry rather than a card that can be drawn.
+    pub fn is_any(&self) -> bool {
+        matches!(
+            self,
+            ResourceCard::Ingredient(IngredientKind::Any)
+                | ResourceCard::Material(MaterialKind::Any)
+        )
+    }
+
+    /// Whether `self` can be spent on a cost entry of `requirement`. `Any` requirements accept
+    /// every card of the same category.
+    pub fn satisfies(&self, requirement: &ResourceCard) -> bool {
+        match (self, requirement) {
+            (ResourceCard::Ingredient(_), ResourceCard::Ingredient(IngredientKind::Any)) => {
+                !self.is_any()
+            }
+            (ResourceCard::Material(_), ResourceCard::Material(MaterialKind::Any)) => {
+                !self.is_any()
+            }
+            _ => self == requirement,
+        }
+    }
+}
+
+impl ResourceCounts {
+    /// No cards.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds one copy of `card`.
+    pub fn add(&mut self, card: ResourceCard) {
+        self.add_n(card, 1);
+    }
+
+    /// Adds `n` copies of `card`.
+    pub fn add_n(&mut self, card: ResourceCard, n: usize) {
+        if n > 0 {
+            *self.counts.entry(card).or_insert(0) += n;
+        }
+    }
+
+    /// Removes one copy of `card`, returning whether there was one.
+    pub fn remove(&mut self, card: &ResourceCard) -> bool {
+        match self.counts.get_mut(card) {
+            Some(n) if *n > 1 => {
+                *n -= 1;
+                true
+            }
+            Some(_) => {
+                self.counts.remove(card);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Number of copies of `card`.
+    pub fn count(&self, card: &ResourceCard) -> usize {
+        self.counts.get(card).copied().unwrap_or(0)
+    }
+
+    /// Total number of cards.
+    pub fn len(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Whether there are no cards at all.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Distinct cards with their counts, in card order.
+    pub fn iter(&self) -> impl Iterator<Item = (ResourceCard, usize)> + '_ {
+        self.counts.iter().map(|(card, n)| (*card, *n))
+    }
+
+    /// Every card, repeated as many times as it is counted.
+    pub fn cards(&self) -> impl Iterator<Item = ResourceCard> + '_ {
+        self.iter()
+            .flat_map(|(card, n)| std::iter::repeat_n(card, n))
+    }
+
+    /// Cards of `self` that are not matched by a card of `other`.
+    pub fn difference(&self, other: &ResourceCounts) -> ResourceCounts {
+        let mut difference = ResourceCounts::new();
+        for (card, n) in self.iter() {
+            difference.add_n(card, n.saturating_sub(other.count(&card)));
+        }
+        difference
+    }
+
+    /// Whether these cards can pay for `cost`, with `Any` entries of the cost accepting any
+    /// card of their category.
+    pub fn covers(&self, cost: &ResourceCounts) -> bool {
+        let exact_covered = cost
+            .iter()
+            .filter(|(card, _)| !card.is_any())
+            .all(|(card, n)| self.count(&card) >= n);
+        exact_covered
+            && cost
+                .iter()
+                .filter(|(card, _)| card.is_any())
+                .all(|(any, n)| {
+                    let spare: usize = self
+                        .iter()
+                        .filter(|(card, _)| card.satisfies(&any))
+                        .map(|(card, m)| m.saturating_sub(cost.count(&card)))
+                        .sum();
+                    spare >= n
+                })
+    }
+}
+
+impl FromIterator<ResourceCard> for ResourceCounts {
+    fn from_iter<I: IntoIterator<Item = ResourceCard>>(iter: I) -> Self {
+        let mut counts = ResourceCounts::new();
+        counts.extend(iter);
+        counts
+    }
+}
+
+impl Extend<ResourceCard> for ResourceCounts {
+    fn extend<I: IntoIterator<Item = ResourceCard>>(&mut self, iter: I) {
+        for card in iter {
+            self.add(card);
+        }
+    }
+}
+
+const INGREDIENT_ANY: usize = IngredientKind::Any as usize;
+const MATERIAL_ANY: usize = 6 + MaterialKind::Any as usize;
+
+impl CompactHand {
+    /// An empty hand.
+    pub fn new() -> Self {
+        Self {
+            counts: [0; RESOURCE_KINDS],
+            ingredients: 0,
+            materials: 0,
+        }
+    }
+
+    /// Adds one copy of `card`.
+    pub fn add(&mut self, card: ResourceCard) {
+        self.counts[card.kind_index()] += 1;
+        match card {
+            ResourceCard::Ingredient(_) => self.ingredients += 1,
+            ResourceCard::Material(_) => self.materials += 1,
+            ResourceCard::Fortune(_) => {}
+        }
+    }
+
+    /// Removes one copy of `card`, returning whether there was one.
+    pub fn remove(&mut self, card: ResourceCard) -> bool {
+        let count = &mut self.counts[card.kind_index()];
+        if *count == 0 {
+            return false;
+        }
+        *count -= 1;
+        match card {
+            ResourceCard::Ingredient(_) => self.ingredients -= 1,
+            ResourceCard::Material(_) => self.materials -= 1,
+            ResourceCard::Fortune(_) => {}
+        }
+        true
+    }
 
+    /// Number of copies of `card`.
+    pub fn count(&self, card: ResourceCard) -> usize {
+        self.counts[card.kind_index()] as usize
+    }
+
+    /// Whether these cards can pay for `cost`, with the `Any` entries of the cost accepting any
+    /// card of their category that is not already spent on an exact entry.
+    pub fn covers(&self, cost: &CompactHand) -> bool {
+        let mut exact_ingredients = 0;
+        let mut exact_materials = 0;
+        for i in 0..RESOURCE_KINDS {
+            if i == INGREDIENT_ANY || i == MATERIAL_ANY {
+                continue;
+            }
+            if self.counts[i] < cost.counts[i] {
+                return false;
+            }
+            if i < INGREDIENT_ANY {
+                exact_ingredients += cost.counts[i];
+            } else if i < MATERIAL_ANY {
+                exact_materials += cost.counts[i];
+            }
+        }
+        self.ingredients - exact_ingredients >= cost.counts[INGREDIENT_ANY]
+            && self.materials - exact_materials >= cost.counts[MATERIAL_ANY]
+    }
+}
+
+impl Default for CompactHand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for QuestCard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuestCard")
+            .field("name", &self.name())
+            .field("cost", &self.cost())
+            .field("reward", &self.reward())
+            .finish()
+    }
+}
+
+/// Quests are written out in full for readers of the JSON, and read back from their id alone.
+impl Serialize for QuestCard {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut quest = serializer.serialize_struct("QuestCard", 4)?;
+        quest.serialize_field("id", &self.id)?;
+        quest.serialize_field("name", self.name())?;
+        quest.serialize_field("cost", self.cost())?;
+        quest.serialize_field("reward", &self.reward())?;
+        quest.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for QuestCard {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Id {
+            id: u8,
+        }
+        let Id { id } = Id::deserialize(deserializer)?;
+        match QuestId::new(id) {
+            Some(id) => Ok(QuestCard { id }),
+            None => Err(serde::de::Error::custom(format!("unknown quest id {}", id))),
+        }
+    }
+}
+
+impl TryFrom<u8> for QuestId {
+    type Error = String;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        QuestId::new(id).ok_or_else(|| format!("unknown quest id {}", id))
+    }
+}
+
+impl From<QuestId> for u8 {
+    fn from(id: QuestId) -> u8 {
+        id.0
+    }
+}
+
+impl FromIterator<ResourceCard> for CompactHand {
+    fn from_iter<I: IntoIterator<Item = ResourceCard>>(iter: I) -> Self {
+        let mut hand = CompactHand::new();
+        for card in iter {
+            hand.add(card);
+        }
+        hand
+    }
+}
+
+impl QuestId {
+    /// The quest at `index` in the registry, or `None` if there is no such quest.
+    pub fn new(index: u8) -> Option<QuestId> {
+        ((index as usize) < QUESTS.len()).then_some(QuestId(index))
+    }
+
+    /// Every quest of the registry, in registry order.
+    pub fn all() -> impl Iterator<Item = QuestId> {
+        (0..QUESTS.len() as u8).map(QuestId)
+    }
+
+    /// Position of the quest in the registry.
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+    /// The registry entry of this quest.
+    pub fn data(&self) -> &'static QuestData {
+        &QUESTS[self.0 as usize]
+    }
+}
+
+impl QuestCard {
+    /// The quest's name, which no other quest shares.
+    pub fn name(&self) -> &'static str {
+        self.id.data().name
+    }
+
+    /// Resource cards paid to complete the quest, where `Any` entries accept any card of their
+    /// category.
+    pub fn cost(&self) -> &'static [ResourceCard] {
+        self.id.data().cost
+    }
+
+    /// Points scored for completing the quest.
+    pub fn reward(&self) -> usize {
+        self.id.data().reward
+    }
+
+    /// The cost as a `CompactHand`, to check against other compact hands with `covers`.
+    pub fn compact_cost(&self) -> CompactHand {
+        self.cost().iter().copied().collect()
+    }
+
+    /// The cost as a multiset, to check against hands with `ResourceCounts::covers`.
+    pub fn cost_counts(&self) -> ResourceCounts {
+        self.cost().iter().copied().collect()
+    }
+
+    /// Whether `hand` can pay the cost of the quest.
+    pub fn can_be_paid_with(&self, hand: &[ResourceCard]) -> bool {
+        self.payment_from(hand).is_some()
+    }
+
+    /// Indices into `hand` of the cards that pay for this quest, or `None` if the hand cannot
+    /// afford it.
+    pub fn payment_from(&self, hand: &[ResourceCard]) -> Option<Vec<usize>> {
+        self.match_cost(hand).into_iter().collect()
+    }
+
+    /// Number of cost entries of this quest that `hand` cannot pay for.
+    pub fn missing_from(&self, hand: &[ResourceCard]) -> usize {
+        self.match_cost(hand).iter().filter(|i| i.is_none()).count()
+    }
+
+    /// For each cost entry, the index into `hand` of the card paying for it, if any is left.
+    fn match_cost(&self, hand: &[ResourceCard]) -> Vec<Option<usize>> {
+        let mut used = vec![false; hand.len()];
+        // Exact requirements first, so that `Any` only consumes what is left over.
+        let mut requirements: Vec<&ResourceCard> = self.cost().iter().collect();
+        requirements.sort_by_key(|r| r.is_any());
+        requirements
+            .into_iter()
+            .map(|requirement| {
+                let i = (0..hand.len()).find(|&i| !used[i] && hand[i].satisfies(requirement))?;
+                used[i] = true;
+                Some(i)
+            })
+            .collect()
+    }
+}
+
+/// Every quest in the game. A `QuestId` is an index into this table.
+static QUESTS: [QuestData; 22] = [
+    QuestData {
+        name: "Tuna Sandwich",
+        cost: &[
+            ResourceCard::Ingredient(IngredientKind::Fish),
+            ResourceCard::Ingredient(IngredientKind::Wheat),
+            ResourceCard::Ingredient(IngredientKind::Any),
+        ],
+        reward: 3,
+    },
+    QuestData {
+        name: "Treehouse",
+        cost: &[
+            ResourceCard::Material(MaterialKind::Bamboo),
+            ResourceCard::Material(MaterialKind::Cotton),
+            ResourceCard::Material(MaterialKind::Leaf),
+            ResourceCard::Material(MaterialKind::Any),
+        ],
+        reward: 4,
+    },
+    QuestData {
+        name: "Tiny Home",
+        cost: &[
+            ResourceCard::Material(MaterialKind::Bamboo),
+            ResourceCard::Material(MaterialKind::Silk),
+            ResourceCard::Material(MaterialKind::Pebble),
+            ResourceCard::Material(MaterialKind::Any),
+        ],
+        reward: 4,
+    },
+    QuestData {
+        name: "Tent",
+        cost: &[
+            ResourceCard::Material(MaterialKind::Bamboo),
+            ResourceCard::Material(MaterialKind::Cotton),
+            ResourceCard::Material(MaterialKind::Leaf),
+            ResourceCard::Material(MaterialKind::Any),
+        ],
+        reward: 4,
+    },
+    QuestData {
+        name: "Tea",
+        cost: &[
+            ResourceCard::Ingredient(IngredientKind::Raindrop),
+            ResourceCard::Ingredient(IngredientKind::Raindrop),
+            ResourceCard::Material(MaterialKind::Leaf),
+            ResourceCard::Ingredient(IngredientKind::Any),
+        ],
+        reward: 4,
+    },
+    QuestData {
+        name: "Sweater",
+        cost: &[
+            ResourceCard::Material(MaterialKind::Cotton),
+            ResourceCard::Material(MaterialKind::Silk),
+            ResourceCard::Material(MaterialKind::Any),
+            ResourceCard::Material(MaterialKind::Any),
+        ],
+        reward: 3,
+    },
+    QuestData {
+        name: "Sushi",
+        cost: &[
+            ResourceCard::Ingredient(IngredientKind::Fish),
+            ResourceCard::Ingredient(IngredientKind::Rice),
+            ResourceCard::Ingredient(IngredientKind::Any),
+        ],
+        reward: 3,
+    },
+    QuestData {
+        name: "Soup",
+        cost: &[
+            ResourceCard::Ingredient(IngredientKind::Raindrop),
+            ResourceCard::Ingredient(IngredientKind::Any),
+            ResourceCard::Ingredient(IngredientKind::Any),
+        ],
+        reward: 2,
+    },
+    QuestData {
+        name: "Snow Cone",
+        cost: &[
+            ResourceCard::Material(MaterialKind::Snowball),
+            ResourceCard::Material(MaterialKind::Snowball),
+            ResourceCard::Ingredient(IngredientKind::Berries),
+            ResourceCard::Ingredient(IngredientKind::Any),
+        ],
+        reward: 4,
+    },
+    QuestData {
+        name: "Ramen",
+        cost: &[
+            ResourceCard::Ingredient(IngredientKind::Raindrop),
+            ResourceCard::Ingredient(IngredientKind::Wheat),
+            ResourceCard::Ingredient(IngredientKind::Any),
+        ],
+        reward: 3,
+    },
+    QuestData {
+        name: "Map",
+        cost: &[
+            ResourceCard::Ingredient(IngredientKind::Rice),
+            ResourceCard::Material(MaterialKind::Leaf),
+            ResourceCard::Material(MaterialKind::Pebble),
+            ResourceCard::Material(MaterialKind::Any),
+        ],
+        reward: 4,
+    },
+    QuestData {
+        name: "Lean-To",
+        cost: &[
+            ResourceCard::Material(MaterialKind::Bamboo),
+            ResourceCard::Material(MaterialKind::Rope),
+            ResourceCard::Material(MaterialKind::Pebble),
+            ResourceCard::Material(MaterialKind::Any),
+        ],
+        reward: 4,
+    },
+    QuestData {
+        name: "Igloo",
+        cost: &[
+            ResourceCard::Material(MaterialKind::Snowball),
+            ResourceCard::Material(MaterialKind::Snowball),
+            ResourceCard::Material(MaterialKind::Cotton),
+            ResourceCard::Material(MaterialKind::Any),
+        ],
+        reward: 4,
+    },
+    QuestData {
+        name: "House Boat",
+        cost: &[
+            ResourceCard::Material(MaterialKind::Silk),
+            ResourceCard::Material(MaterialKind::Bamboo),
+            ResourceCard::Material(MaterialKind::Leaf),
+            ResourceCard::Material(MaterialKind::Any),
+            ResourceCard::Material(MaterialKind::Any),
+        ],
+        reward: 5,
+    },
+    QuestData {
+        name: "Hair Brush",
+        cost: &[
+            ResourceCard::Material(MaterialKind::Rope),
+            ResourceCard::Material(MaterialKind::Bamboo),
+            ResourceCard::Material(MaterialKind::Any),
+            ResourceCard::Material(MaterialKind::Any),
+        ],
+        reward: 3,
+    },
+    QuestData {
+        name: "Fruit Salad",
+        cost: &[
+            ResourceCard::Ingredient(IngredientKind::Berries),
+            ResourceCard::Ingredient(IngredientKind::Berries),
+            ResourceCard::Ingredient(IngredientKind::Any),
+        ],
+        reward: 3,
+    },
+    QuestData {
+        name: "Fishing Rod",
+        cost: &[
+            ResourceCard::Material(MaterialKind::Rope),
+            ResourceCard::Material(MaterialKind::Bamboo),
+            ResourceCard::Ingredient(IngredientKind::Any),
+            ResourceCard::Ingredient(IngredientKind::Any),
+        ],
+        reward: 3,
+    },
+    QuestData {
+        name: "Curry",
+        cost: &[
+            ResourceCard::Ingredient(IngredientKind::Rice),
+            ResourceCard::Ingredient(IngredientKind::Any),
+            ResourceCard::Ingredient(IngredientKind::Any),
+        ],
+        reward: 2,
+    },
+    QuestData {
+        name: "Cave Hotel",
+        cost: &[
+            ResourceCard::Material(MaterialKind::Pebble),
+            ResourceCard::Material(MaterialKind::Silk),
+            ResourceCard::Material(MaterialKind::Leaf),
+            ResourceCard::Material(MaterialKind::Any),
+            ResourceCard::Material(MaterialKind::Any),
+        ],
+        reward: 5,
+    },
+    QuestData {
+        name: "Castle",
+        cost: &[
+            ResourceCard::Material(MaterialKind::Pebble),
+            ResourceCard::Material(MaterialKind::Silk),
+            ResourceCard::Material(MaterialKind::Rope),
+            ResourceCard::Material(MaterialKind::Any),
+        ],
+        reward: 4,
+    },
+    QuestData {
+        name: "Beary Pie",
+        cost: &[
+            ResourceCard::Ingredient(IngredientKind::Berries),
+            ResourceCard::Ingredient(IngredientKind::Wheat),
+            ResourceCard::Ingredient(IngredientKind::Any),
+        ],
+        reward: 3,
+    },
+    QuestData {
+        name: "Bao",
+        cost: &[
+            ResourceCard::Ingredient(IngredientKind::Wheat),
+            ResourceCard::Ingredient(IngredientKind::Any),
+            ResourceCard::Ingredient(IngredientKind::Any),
+        ],
+        reward: 2,
+    },
+];
+
+/// One card and one tile of every quest, in registry order.
+pub fn build_all_quests() -> (Vec<QuestCard>, Vec<QuestTile>) {
+    let quests: Vec<QuestCard> = QuestId::all().map(|id| QuestCard { id }).collect();
+    let quest_tiles = quests.iter().map(|&quest| QuestTile { quest }).collect();
     (quests, quest_tiles)
 }
 
+/// The score of a player who completed `completed_quests`: the sum of their rewards.
+pub fn score(completed_quests: &[QuestCard]) -> usize {
+    completed_quests.iter().map(|q| q.reward()).sum()
+}
+
+/// Every card of the resource deck, unshuffled.
 pub fn build_all_resources() -> Vec<ResourceCard> {
     let mut resources = Vec::new();
     let mut insert_resource_cards = |resource: ResourceCard, count: u8| {
@@ -323,3 +805,116 @@ pub fn build_all_resources() -> Vec<ResourceCard> {
 
     resources
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+    const FISH: ResourceCard = ResourceCard::Ingredient(IngredientKind::Fish);
+    const WHEAT: ResourceCard = ResourceCard::Ingredient(IngredientKind::Wheat);
+    const ANY_INGREDIENT: ResourceCard = ResourceCard::Ingredient(IngredientKind::Any);
+    const ROPE: ResourceCard = ResourceCard::Material(MaterialKind::Rope);
+    const ANY_MATERIAL: ResourceCard = ResourceCard::Material(MaterialKind::Any);
+    const PICNIC: ResourceCard = ResourceCard::Fortune(FortuneKind::Picnic);
+
+    fn counts(cards: &[ResourceCard]) -> ResourceCounts {
+        cards.iter().copied().collect()
+    }
+
+    #[test]
+    fn counts_cover_exact_costs() {
+        assert!(counts(&[]).covers(&counts(&[])));
+        assert!(counts(&[FISH, WHEAT]).covers(&counts(&[FISH])));
+        assert!(counts(&[FISH, FISH]).covers(&counts(&[FISH, FISH])));
+        assert!(!counts(&[FISH]).covers(&counts(&[FISH, FISH])));
+        assert!(!counts(&[WHEAT]).covers(&counts(&[FISH])));
+    }
+
+    #[test]
+    fn counts_cover_any_with_spare_cards_of_the_category() {
+        assert!(counts(&[WHEAT]).covers(&counts(&[ANY_INGREDIENT])));
+        assert!(counts(&[FISH, WHEAT]).covers(&counts(&[FISH, ANY_INGREDIENT])));
+        assert!(counts(&[ROPE, FISH]).covers(&counts(&[ANY_INGREDIENT, ANY_MATERIAL])));
+        // The fish paying the exact entry cannot also pay for the wildcard.
+        assert!(!counts(&[FISH]).covers(&counts(&[FISH, ANY_INGREDIENT])));
+        assert!(!counts(&[ROPE]).covers(&counts(&[ANY_INGREDIENT])));
+        assert!(!counts(&[PICNIC]).covers(&counts(&[ANY_MATERIAL])));
+    }
+
+    #[test]
+    fn compact_hands_agree_with_payment_for_every_quest() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut resources = build_all_resources();
+        let (quests, _) = build_all_quests();
+        for size in 0..12 {
+            for _ in 0..50 {
+                resources.shuffle(&mut rng);
+                let hand = &resources[..size];
+                let compact: CompactHand = hand.iter().copied().collect();
+                let counts: ResourceCounts = hand.iter().copied().collect();
+                for quest in &quests {
+                    let payable = quest.can_be_paid_with(hand);
+                    assert_eq!(
+                        compact.covers(&quest.compact_cost()),
+                        payable,
+                        "{:?}",
+                        quest
+                    );
+                    assert_eq!(counts.covers(&quest.cost_counts()), payable, "{:?}", quest);
+                    assert_eq!(quest.missing_from(hand) == 0, payable, "{:?}", quest);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn missing_cards_count_unpaid_cost_entries() {
+        let (quests, _) = build_all_quests();
+        for quest in &quests {
+            let cost = quest.cost();
+            assert_eq!(quest.missing_from(&[]), cost.len());
+            // Wildcards in the cost stand for any card of their kind.
+            let hand: Vec<ResourceCard> = cost
+                .iter()
+                .map(|card| match card {
+                    ResourceCard::Ingredient(IngredientKind::Any) => {
+                        ResourceCard::Ingredient(IngredientKind::Raindrop)
+                    }
+                    ResourceCard::Material(MaterialKind::Any) => {
+                        ResourceCard::Material(MaterialKind::Pebble)
+                    }
+                    card => *card,
+                })
+                .collect();
+            assert_eq!(quest.missing_from(&hand), 0, "{:?}", quest);
+            assert_eq!(quest.missing_from(&hand[1..]), 1, "{:?}", quest);
+        }
+    }
+
+    #[test]
+    fn compact_hands_count_and_remove_cards() {
+        let mut hand: CompactHand = [FISH, FISH, ROPE].into_iter().collect();
+        assert_eq!(hand.count(FISH), 2);
+        assert!(hand.remove(FISH));
+        assert!(hand.remove(FISH));
+        assert!(!hand.remove(FISH));
+        assert!(hand.covers(&[ANY_MATERIAL].into_iter().collect()));
+        assert!(!hand.covers(&[ANY_INGREDIENT].into_iter().collect()));
+    }
+
+    #[test]
+    fn quest_ids_only_name_registered_quests() {
+        assert_eq!(QuestId::new(0), QuestId::all().next());
+        assert_eq!(QuestId::new(QUESTS.len() as u8), None);
+        assert!(QuestId::all().all(|id| QuestId::new(id.index() as u8) == Some(id)));
+
+        let quest = QuestCard {
+            id: QuestId::new(1).unwrap(),
+        };
+        let json = serde_json::to_string(&quest).unwrap();
+        assert_eq!(serde_json::from_str::<QuestCard>(&json).unwrap(), quest);
+        assert!(serde_json::from_str::<QuestCard>(r#"{"id": 200}"#).is_err());
+        assert!(serde_json::from_str::<QuestId>("200").is_err());
+    }
+}
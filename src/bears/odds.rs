@@ -0,0 +1,238 @@
+//! Exact odds of completing quests, for hints and analysis.
+
+use super::models::*;
+use std::collections::HashMap;
+
+/// Exact probability that `hand`, together with `draws` more cards drawn without replacement from
+/// `remaining`, can pay for `quest`. `remaining` is the composition of the cards the player has not
+/// seen yet, in any order.
+pub fn probability_to_complete(
+    hand: &[ResourceCard],
+    quest: &QuestCard,
+    remaining: &[ResourceCard],
+    draws: usize,
+) -> f64 {
+    if quest.can_be_paid_with(hand) {
+        return 1.0;
+    }
+    let draws = draws.min(remaining.len());
+    let shortfall = Shortfall::of(hand, quest);
+    let groups = shortfall.groups(remaining);
+
+    // Number of ways to draw each (draw count, per group capped count) combination.
+    let mut ways: HashMap<(usize, Vec<usize>), f64> = HashMap::new();
+    ways.insert((0, Vec::new()), 1.0);
+    for group in &groups {
+        let mut next: HashMap<(usize, Vec<usize>), f64> = HashMap::new();
+        for ((drawn, counts), w) in ways {
+            for k in 0..=group.size.min(draws - drawn) {
+                let mut counts = counts.clone();
+                counts.push(k.min(group.cap));
+                *next.entry((drawn + k, counts)).or_insert(0.0) += w * binomial(group.size, k);
+            }
+        }
+        ways = next;
+    }
+
+    let favourable: f64 = ways
+        .into_iter()
+        .filter(|((drawn, counts), _)| *drawn == draws && shortfall.is_covered_by(&groups, counts))
+        .map(|(_, w)| w)
+        .sum();
+    favourable / binomial(remaining.len(), draws)
+}
+
+/// What a hand is still missing for a quest: exact cards, and `Any` slots per category.
+struct Shortfall {
+    exact: ResourceCounts,
+    any: ResourceCounts,
+}
+
+/// Cards from the remaining deck that matter in the same way. `cap` is the count beyond which
+/// drawing more of them cannot help.
+struct Group {
+    card: Option<ResourceCard>,
+    any: Option<ResourceCard>,
+    size: usize,
+    cap: usize,
+}
+
+impl Shortfall {
+    fn of(hand: &[ResourceCard], quest: &QuestCard) -> Self {
+        let cost = quest.cost_counts();
+        let mut unused: ResourceCounts = hand.iter().copied().collect();
+        let mut exact = ResourceCounts::new();
+        for (card, n) in cost.iter().filter(|(card, _)| !card.is_any()) {
+            let have = unused.count(&card).min(n);
+            exact.add_n(card, n - have);
+            for _ in 0..have {
+                unused.remove(&card);
+            }
+        }
+        let mut any = ResourceCounts::new();
+        for (requirement, n) in cost.iter().filter(|(card, _)| card.is_any()) {
+            let spare: usize = unused
+                .iter()
+                .filter(|(card, _)| card.satisfies(&requirement))
+                .map(|(_, m)| m)
+                .sum();
+            any.add_n(requirement, n.saturating_sub(spare));
+        }
+        Self { exact, any }
+    }
+
+    fn any_needed_for(&self, card: &ResourceCard) -> Option<(ResourceCard, usize)> {
+        self.any.iter().find(|(a, _)| card.satisfies(a))
+    }
+
+    fn groups(&self, remaining: &[ResourceCard]) -> Vec<Group> {
+        let mut groups: Vec<Group> = self
+            .exact
+            .iter()
+            .map(|(card, n)| {
+                let any = self.any_needed_for(&card);
+                Group {
+                    card: Some(card),
+                    any: any.map(|(a, _)| a),
+                    size: 0,
+                    cap: n + any.map_or(0, |(_, m)| m),
+                }
+            })
+            .collect();
+        groups.extend(self.any.iter().map(|(a, n)| Group {
+            card: None,
+            any: Some(a),
+            size: 0,
+            cap: n,
+        }));
+        let mut irrelevant = Group {
+            card: None,
+            any: None,
+            size: 0,
+            cap: 0,
+        };
+        for card in remaining {
+            let group = match groups.iter().position(|g| g.card == Some(*card)) {
+                Some(i) => &mut groups[i],
+                None => match self.any_needed_for(card) {
+                    Some((a, _)) => groups
+                        .iter_mut()
+                        .find(|g| g.card.is_none() && g.any == Some(a))
+                        .unwrap(),
+                    None => &mut irrelevant,
+                },
+            };
+            group.size += 1;
+        }
+        groups.push(irrelevant);
+        groups
+    }
+
+    fn is_covered_by(&self, groups: &[Group], counts: &[usize]) -> bool {
+        let mut spare = ResourceCounts::new();
+        for (group, count) in groups.iter().zip(counts) {
+            let surplus = match group.card {
+                Some(card) => {
+                    let needed = self.exact.count(&card);
+                    if *count < needed {
+                        return false;
+                    }
+                    count - needed
+                }
+                None => *count,
+            };
+            if let Some(a) = group.any {
+                spare.add_n(a, surplus);
+            }
+        }
+        self.any.iter().all(|(a, needed)| spare.count(&a) >= needed)
+    }
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    let k = k.min(n - k);
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    /// Share of the `draws`-card subsets of `remaining` that complete the quest, by listing them.
+    fn brute_force(
+        hand: &[ResourceCard],
+        quest: &QuestCard,
+        remaining: &[ResourceCard],
+        draws: usize,
+    ) -> f64 {
+        let mut favourable = 0;
+        let mut total = 0;
+        for mask in 0u32..1 << remaining.len() {
+            if mask.count_ones() as usize != draws {
+                continue;
+            }
+            let mut cards = hand.to_vec();
+            cards.extend(
+                (0..remaining.len())
+                    .filter(|i| mask & 1 << i != 0)
+                    .map(|i| remaining[i]),
+            );
+            total += 1;
+            if quest.can_be_paid_with(&cards) {
+                favourable += 1;
+            }
+        }
+        favourable as f64 / total as f64
+    }
+
+    #[test]
+    fn matches_brute_force_on_small_decks() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut resources = build_all_resources();
+        for quest in QuestId::all().map(|id| QuestCard { id }) {
+            resources.shuffle(&mut rng);
+            let hand = &resources[..2];
+            let remaining = &resources[2..12];
+            for draws in 0..=4 {
+                let exact = probability_to_complete(hand, &quest, remaining, draws);
+                let expected = brute_force(hand, &quest, remaining, draws);
+                assert!(
+                    (exact - expected).abs() < 1e-9,
+                    "{} with {} draws: {} != {}",
+                    quest.name(),
+                    draws,
+                    exact,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn affordable_quests_are_certain() {
+        let quest = QuestCard {
+            id: QuestId::all().next().unwrap(),
+        };
+        let hand: Vec<ResourceCard> = quest
+            .cost()
+            .iter()
+            .map(|card| match card {
+                ResourceCard::Ingredient(IngredientKind::Any) => {
+                    ResourceCard::Ingredient(IngredientKind::Rice)
+                }
+                ResourceCard::Material(MaterialKind::Any) => {
+                    ResourceCard::Material(MaterialKind::Pebble)
+                }
+                card => *card,
+            })
+            .collect();
+        assert_eq!(probability_to_complete(&hand, &quest, &[], 0), 1.0);
+        assert_eq!(probability_to_complete(&[], &quest, &[], 3), 0.0);
+    }
+}
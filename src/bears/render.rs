@@ -0,0 +1,315 @@
+//! Human-readable text for cards, players, tables and events, optionally with ANSI colors.
+//!
+//! The `Display` implementations render without color and name players by index:
+//!
+//! ```
+//! use pawlice_academy::bears::models::{QuestCard, QuestId};
+//!
+//! let quest = QuestCard { id: QuestId::new(0).unwrap() };
+//! assert_eq!(quest.to_string(), "Tuna Sandwich: Fish + Wheat + any ingredient → 3");
+//! ```
+
+use super::models::{
+    BearCard, IngredientKind, MaterialKind, QuestCard, ResourceCard, ResourceCounts,
+};
+use super::spectator::SpectatorEvent;
+use super::system::{GameState, GameTable, LoggedEvents, Player};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::IsTerminal;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+
+/// Renders the game as text. Players are named by their bear once the renderer knows it.
+#[derive(Debug, Clone, Default)]
+pub struct Renderer {
+    /// Color cards by kind with ANSI escape codes.
+    pub color: bool,
+    bears: BTreeMap<usize, BearCard>,
+}
+
+impl Renderer {
+    /// Text without escape codes.
+    pub fn plain() -> Self {
+        Self::default()
+    }
+
+    /// Text with ANSI colors.
+    pub fn colored() -> Self {
+        Self {
+            color: true,
+            ..Self::default()
+        }
+    }
+
+    /// Colored when standard output is a terminal and `NO_COLOR` is not set.
+    pub fn for_stdout() -> Self {
+        Self {
+            color: std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            ..Self::default()
+        }
+    }
+
+    /// Names the players of `table` by their bear.
+    pub fn with_bears_of(self, table: &GameTable) -> Self {
+        self.with_bears(table.players.iter().map(|p| (p.index, p.bear)))
+    }
+
+    /// Names each player index by its bear.
+    pub fn with_bears(mut self, bears: impl IntoIterator<Item = (usize, BearCard)>) -> Self {
+        self.bears.extend(bears);
+        self
+    }
+
+    /// `Fish`, `Bee Attack`, or `any ingredient` for a wildcard cost entry.
+    pub fn card(&self, card: &ResourceCard) -> String {
+        let name = match card {
+            ResourceCard::Ingredient(IngredientKind::Any) => "any ingredient".to_string(),
+            ResourceCard::Material(MaterialKind::Any) => "any material".to_string(),
+            ResourceCard::Ingredient(kind) => kind.to_string(),
+            ResourceCard::Material(kind) => kind.to_string(),
+            ResourceCard::Fortune(kind) => spaced(&kind.to_string()),
+        };
+        self.paint(&name, card_color(card))
+    }
+
+    /// A hand grouped by kind, such as `2× Fish, Snowball`.
+    pub fn cards(&self, cards: &[ResourceCard]) -> String {
+        if cards.is_empty() {
+            return "nothing".to_string();
+        }
+        let counts: ResourceCounts = cards.iter().copied().collect();
+        counts
+            .iter()
+            .map(|(card, count)| match count {
+                1 => self.card(&card),
+                _ => format!("{}× {}", count, self.card(&card)),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// `Tuna Sandwich: Fish + Wheat + any ingredient → 3`.
+    pub fn quest(&self, quest: &QuestCard) -> String {
+        let cost: Vec<String> = quest.cost().iter().map(|c| self.card(c)).collect();
+        format!(
+            "{}: {} → {}",
+            quest.name(),
+            cost.join(" + "),
+            quest.reward()
+        )
+    }
+
+    /// `Polar bear`, or `Player 2` for a player whose bear is unknown.
+    pub fn name(&self, player_index: usize) -> String {
+        match self.bears.get(&player_index) {
+            Some(bear) => format!("{} bear", bear),
+            None => format!("Player {}", player_index),
+        }
+    }
+
+    /// A header with the score, then one line each for the hand, every quest tile and the
+    /// completed quests.
+    pub fn player(&self, player: &Player) -> String {
+        let mut lines = vec![
+            self.bold(&format!(
+                "{} bear (player {}), {} points",
+                player.bear,
+                player.index,
+                player.score()
+            )),
+            format!("  Hand: {}", self.cards(&player.hand)),
+        ];
+        lines.extend(
+            player
+                .quest_tiles
+                .iter()
+                .map(|tile| format!("  Quest tile {}", self.quest(&tile.quest))),
+        );
+        if !player.completed_quests.is_empty() {
+            let completed: Vec<String> = player
+                .completed_quests
+                .iter()
+                .map(|q| format!("{} ({})", q.name(), q.reward()))
+                .collect();
+            lines.push(format!("  Completed: {}", completed.join(", ")));
+        }
+        lines.join("\n")
+    }
+
+    /// Whose turn it is, the open quests, the deck sizes and every player.
+    pub fn table(&self, table: &GameTable) -> String {
+        let renderer = self.clone().with_bears_of(table);
+        let mut lines = vec![renderer.bold(&renderer.state(table))];
+        lines.push("Open quests:".to_string());
+        lines.extend(
+            table
+                .open_quests
+                .iter()
+                .map(|quest| format!("  {}", renderer.quest(quest))),
+        );
+        lines.push(format!(
+            "Quest deck {}, resource deck {}, discard pile {}",
+            table.quests.len(),
+            table.resources.len(),
+            table.discard_pile.len()
+        ));
+        lines.extend(table.players.iter().map(|p| renderer.player(p)));
+        lines.join("\n")
+    }
+
+    /// `Turn 12: Polar bear to play`, or who won once the game is done.
+    pub fn state(&self, table: &GameTable) -> String {
+        self.state_of(&table.state, table.turns_played)
+    }
+
+    /// Like `state`, for a table that is no longer at hand.
+    pub fn state_of(&self, state: &GameState, turns_played: usize) -> String {
+        match state {
+            GameState::Uninitialized => "Setting up".to_string(),
+            GameState::PlayerReady { player_index } => format!(
+                "Turn {}: {} to play",
+                turns_played + 1,
+                self.name(*player_index)
+            ),
+            GameState::Done { winner_indices } if winner_indices.is_empty() => {
+                "Game over, the score target was missed".to_string()
+            }
+            GameState::Done { winner_indices } => {
+                format!("Game over, won by {}", self.names(winner_indices))
+            }
+        }
+    }
+
+    /// `Polar bear drew Fish`, and so on for every event.
+    pub fn event(&self, event: &LoggedEvents) -> String {
+        match event {
+            LoggedEvents::AppliedRules { rules } => format!(
+                "Playing with {} starting resources, {} quest tiles each and {} open quests",
+                rules.starting_resources, rules.quest_tiles_per_player, rules.open_quests
+            ),
+            LoggedEvents::BuiltQuestDeck { quests } => {
+                format!("Shuffled {} quests into the quest deck", quests.len())
+            }
+            LoggedEvents::BuiltResourceDeck { resources } => {
+                format!("Shuffled {} cards into the resource deck", resources.len())
+            }
+            LoggedEvents::SelectedInitialPlayer { player_index } => {
+                format!("{} goes first", self.name(*player_index))
+            }
+            LoggedEvents::DrewResource {
+                player_index,
+                resource,
+            } => format!("{} drew {}", self.name(*player_index), self.card(resource)),
+            LoggedEvents::CompletedQuest {
+                player_index,
+                quest,
+            } => format!(
+                "{} completed {} for {}",
+                self.name(*player_index),
+                quest.name(),
+                quest.reward()
+            ),
+            LoggedEvents::Passed { player_index } => format!("{} passed", self.name(*player_index)),
+            LoggedEvents::ExpiredQuest { quest } => format!("{} expired", quest.name()),
+            LoggedEvents::Finished { winner_indices } if winner_indices.is_empty() => {
+                "Game over, nobody won".to_string()
+            }
+            LoggedEvents::Finished { winner_indices } => {
+                format!("Game over, won by {}", self.names(winner_indices))
+            }
+        }
+    }
+
+    /// An event as a spectator sees it.
+    pub fn spectator_event(&self, event: &SpectatorEvent) -> String {
+        match event {
+            SpectatorEvent::Revealed { event } => self.event(event),
+            SpectatorEvent::Hidden { player_index } => {
+                format!("{} drew a card", self.name(*player_index))
+            }
+        }
+    }
+
+    fn names(&self, player_indices: &[usize]) -> String {
+        player_indices
+            .iter()
+            .map(|&i| self.name(i))
+            .collect::<Vec<_>>()
+            .join(" and ")
+    }
+
+    fn bold(&self, text: &str) -> String {
+        self.paint(text, BOLD)
+    }
+
+    fn paint(&self, text: &str, code: &str) -> String {
+        match self.color {
+            true => format!("{}{}{}", code, text, RESET),
+            false => text.to_string(),
+        }
+    }
+}
+
+/// ANSI color of a card: one per ingredient and material, red for fortunes, dim for wildcards.
+fn card_color(card: &ResourceCard) -> &'static str {
+    match card {
+        ResourceCard::Ingredient(kind) => match kind {
+            IngredientKind::Fish => "\x1b[34m",
+            IngredientKind::Berries => "\x1b[35m",
+            IngredientKind::Wheat => "\x1b[33m",
+            IngredientKind::Rice => "\x1b[93m",
+            IngredientKind::Raindrop => "\x1b[36m",
+            IngredientKind::Any => DIM,
+        },
+        ResourceCard::Material(kind) => match kind {
+            MaterialKind::Snowball => "\x1b[97m",
+            MaterialKind::Cotton => "\x1b[37m",
+            MaterialKind::Bamboo => "\x1b[32m",
+            MaterialKind::Rope => "\x1b[91m",
+            MaterialKind::Leaf => "\x1b[92m",
+            MaterialKind::Silk => "\x1b[95m",
+            MaterialKind::Pebble => "\x1b[90m",
+            MaterialKind::Any => DIM,
+        },
+        ResourceCard::Fortune(_) => "\x1b[1;31m",
+    }
+}
+
+/// `BeeAttack` as `Bee Attack`.
+fn spaced(name: &str) -> String {
+    let mut spaced = String::with_capacity(name.len() + 2);
+    for (i, c) in name.chars().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            spaced.push(' ');
+        }
+        spaced.push(c);
+    }
+    spaced
+}
+
+impl fmt::Display for QuestCard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&Renderer::plain().quest(self))
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&Renderer::plain().player(self))
+    }
+}
+
+impl fmt::Display for GameTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&Renderer::plain().table(self))
+    }
+}
+
+impl fmt::Display for LoggedEvents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&Renderer::plain().event(self))
+    }
+}
@@ -0,0 +1,279 @@
+//! The setup numbers of a game, and checks that a game can be dealt with them.
+
+use super::models::{build_all_quests, build_all_resources, BearCard};
+use serde::{Deserialize, Serialize};
+
+/// Score a lone player needs to win against `SoloOpponent::ScoreTarget` with the standard rules.
+pub const SOLO_SCORE_TARGET: usize = 15;
+
+/// The numbers that shape a game. `GameRules::default()` is the standard game, and anything
+/// else is a variant for experiments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRules {
+    pub min_players: usize,
+    pub max_players: usize,
+    /// Resource cards dealt to every seat during setup.
+    pub starting_resources: usize,
+    /// Quest tiles dealt to every seat during setup.
+    pub quest_tiles_per_player: usize,
+    /// Quests revealed from the quest deck at a time.
+    pub open_quests: usize,
+    /// `(seats, quests)`: a table of `seats` players plays with `quests` quests. Every allowed
+    /// table size needs an entry.
+    pub quest_deck_sizes: Vec<(usize, usize)>,
+    /// What a lone player plays against.
+    pub solo_opponent: SoloOpponent,
+    /// The game ends once this many turns have been played in total, if set.
+    pub turn_limit: Option<usize>,
+}
+
+/// What a lone player plays against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SoloOpponent {
+    /// The player wins by scoring at least this much before the game ends.
+    ScoreTarget(usize),
+    /// A greedy bear takes the second seat, and the player has to outscore it.
+    Automaton,
+}
+
+/// Why a game cannot be set up with some rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulesError {
+    InvalidPlayerRange {
+        min_players: usize,
+        max_players: usize,
+    },
+    MissingQuestDeckSize {
+        seats: usize,
+    },
+    NotEnoughResources {
+        needed: usize,
+        available: usize,
+    },
+    NotEnoughQuestTiles {
+        needed: usize,
+        available: usize,
+    },
+    NotEnoughQuests {
+        needed: usize,
+        available: usize,
+    },
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            min_players: 1,
+            max_players: 6,
+            starting_resources: 5,
+            quest_tiles_per_player: 3,
+            open_quests: 3,
+            quest_deck_sizes: vec![(1, 12), (2, 12), (3, 12), (4, 15), (5, 15), (6, 22)],
+            solo_opponent: SoloOpponent::ScoreTarget(SOLO_SCORE_TARGET),
+            turn_limit: None,
+        }
+    }
+}
+
+impl GameRules {
+    /// Checks that a game can be set up with these rules for every allowed number of players,
+    /// counting the seat of a solo automaton.
+    pub fn validate(&self) -> Result<(), RulesError> {
+        if self.min_players < 1
+            || self.min_players > self.max_players
+            || self.max_players > BearCard::all().len()
+        {
+            return Err(RulesError::InvalidPlayerRange {
+                min_players: self.min_players,
+                max_players: self.max_players,
+            });
+        }
+        let resources = build_all_resources().len();
+        let (quests, quest_tiles) = build_all_quests();
+        for num_players in self.min_players..=self.max_players {
+            let seats = self.seats(num_players);
+            if self.starting_resources * seats > resources {
+                return Err(RulesError::NotEnoughResources {
+                    needed: self.starting_resources * seats,
+                    available: resources,
+                });
+            }
+            if self.quest_tiles_per_player * seats > quest_tiles.len() {
+                return Err(RulesError::NotEnoughQuestTiles {
+                    needed: self.quest_tiles_per_player * seats,
+                    available: quest_tiles.len(),
+                });
+            }
+            let deck_size = self
+                .quest_deck_size(seats)
+                .ok_or(RulesError::MissingQuestDeckSize { seats })?;
+            if deck_size > quests.len() || deck_size < self.open_quests {
+                return Err(RulesError::NotEnoughQuests {
+                    needed: deck_size.max(self.open_quests),
+                    available: quests.len().min(deck_size),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a game may have `num_players` players.
+    pub fn allows(&self, num_players: usize) -> bool {
+        (self.min_players..=self.max_players).contains(&num_players)
+    }
+
+    /// Number of quests in the quest deck for a table of `seats` players.
+    pub fn quest_deck_size(&self, seats: usize) -> Option<usize> {
+        self.quest_deck_sizes
+            .iter()
+            .find(|(n, _)| *n == seats)
+            .map(|(_, quests)| *quests)
+    }
+
+    /// Number of seats at the table for `num_players` players, counting a solo automaton.
+    pub fn seats(&self, num_players: usize) -> usize {
+        match (num_players, self.solo_opponent) {
+            (1, SoloOpponent::Automaton) => 2,
+            _ => num_players,
+        }
+    }
+}
+
+impl std::fmt::Display for RulesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RulesError::InvalidPlayerRange {
+                min_players,
+                max_players,
+            } => write!(
+                f,
+                "Invalid player range {}..={}: tables seat 1 to {} players",
+                min_players,
+                max_players,
+                BearCard::all().len()
+            ),
+            RulesError::MissingQuestDeckSize { seats } => {
+                write!(f, "No quest deck size is given for {} seats", seats)
+            }
+            RulesError::NotEnoughResources { needed, available } => write!(
+                f,
+                "Dealing starting resources needs {} cards but there are only {}",
+                needed, available
+            ),
+            RulesError::NotEnoughQuestTiles { needed, available } => write!(
+                f,
+                "Dealing quest tiles needs {} tiles but there are only {}",
+                needed, available
+            ),
+            RulesError::NotEnoughQuests { needed, available } => write!(
+                f,
+                "The quest deck needs {} quests but only {} are available",
+                needed, available
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_rules_are_valid() {
+        assert_eq!(GameRules::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_impossible_player_ranges() {
+        for (min_players, max_players) in [(0, 4), (4, 3), (1, 7)] {
+            let rules = GameRules {
+                min_players,
+                max_players,
+                ..GameRules::default()
+            };
+            assert_eq!(
+                rules.validate(),
+                Err(RulesError::InvalidPlayerRange {
+                    min_players,
+                    max_players
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_dealing_more_than_there_is() {
+        let rules = GameRules {
+            starting_resources: 30,
+            ..GameRules::default()
+        };
+        assert!(matches!(
+            rules.validate(),
+            Err(RulesError::NotEnoughResources { .. })
+        ));
+        let rules = GameRules {
+            quest_tiles_per_player: 4,
+            ..GameRules::default()
+        };
+        assert!(matches!(
+            rules.validate(),
+            Err(RulesError::NotEnoughQuestTiles { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_unusable_quest_decks() {
+        let mut rules = GameRules::default();
+        rules.quest_deck_sizes.retain(|(seats, _)| *seats != 4);
+        assert_eq!(
+            rules.validate(),
+            Err(RulesError::MissingQuestDeckSize { seats: 4 })
+        );
+
+        let rules = GameRules {
+            quest_deck_sizes: vec![(1, 12), (2, 12), (3, 40), (4, 15), (5, 15), (6, 22)],
+            ..GameRules::default()
+        };
+        assert!(matches!(
+            rules.validate(),
+            Err(RulesError::NotEnoughQuests { .. })
+        ));
+
+        let rules = GameRules {
+            open_quests: 13,
+            ..GameRules::default()
+        };
+        assert!(matches!(
+            rules.validate(),
+            Err(RulesError::NotEnoughQuests { .. })
+        ));
+    }
+
+    #[test]
+    fn counts_the_seat_of_a_solo_automaton() {
+        let rules = GameRules {
+            max_players: 1,
+            quest_tiles_per_player: 12,
+            solo_opponent: SoloOpponent::Automaton,
+            ..GameRules::default()
+        };
+        assert_eq!(
+            rules.validate(),
+            Err(RulesError::NotEnoughQuestTiles {
+                needed: 24,
+                available: 22
+            })
+        );
+        let rules = GameRules {
+            solo_opponent: SoloOpponent::ScoreTarget(SOLO_SCORE_TARGET),
+            ..rules
+        };
+        assert_eq!(rules.validate(), Ok(()));
+    }
+
+    #[test]
+    fn six_players_play_with_every_quest() {
+        let rules = GameRules::default();
+        assert_eq!(rules.quest_deck_size(6), Some(build_all_quests().0.len()));
+    }
+}
@@ -0,0 +1,528 @@
+//! A local multiplayer server. The server owns the only `GameTable` and checks every action
+//! against it, while clients only ever see their own `PlayerView`.
+//!
+//! Clients talk to the server over TCP, one JSON message per line: `ClientMessage` from the
+//! clients and `ServerMessage` from the server. Before the game, the table is a lobby where
+//! players take seats, pick bears, fill empty seats with bots and ready up. Once every seat is
+//! taken and every player is ready, the game starts. Players who drop out keep their seat and
+//! can reconnect with the token they were given when they joined.
+//!
+//! Anyone who does not take a seat may spectate instead, and receives the public events of the
+//! game, optionally a few turns late.
+
+use super::models::BearCard;
+use super::rules::GameRules;
+use super::spectator::{Audience, EventStream, SpectatorEvent};
+use super::system::{
+    Action, ActionSelectionStrategy, GameState, GameTable, InitialPlayerSelectionStrategy,
+    QuestTileSelectionStrategy,
+};
+use super::view::PlayerView;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+/// A message from a client to the server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Takes `seat` if it is free, or the first free seat.
+    Join {
+        seat: Option<usize>,
+    },
+    /// Takes back the seat that was given `token`.
+    Reconnect {
+        token: String,
+    },
+    /// Picks a bear nobody else has picked, or `None` to be assigned one when the game starts.
+    PickBear {
+        bear: Option<BearCard>,
+    },
+    /// The game starts once every seat is taken and every human is ready.
+    Ready {
+        ready: bool,
+    },
+    /// Seats a bot in every empty seat.
+    FillWithBots,
+    Act {
+        action: Action,
+    },
+    /// Watches the game without a seat, `delay` turns behind.
+    Spectate {
+        delay: usize,
+    },
+}
+
+/// A message from the server to a client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Keep `token` to reconnect to the seat.
+    Joined {
+        player_index: usize,
+        token: String,
+    },
+    /// Sent to everyone seated whenever the lobby changes.
+    Lobby {
+        seats: Vec<SeatInfo>,
+    },
+    /// Sent to every seated client whenever the table changes.
+    View {
+        view: Box<PlayerView>,
+    },
+    /// Sent to spectators whenever events are released to them.
+    Events {
+        events: Vec<SpectatorEvent>,
+    },
+    Error {
+        message: String,
+    },
+    /// Scores are by player index. Sent to spectators as well.
+    Done {
+        winner_indices: Vec<usize>,
+        scores: Vec<usize>,
+    },
+}
+
+/// A seat as everyone in the lobby sees it. Seats are numbered by player index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeatInfo {
+    pub occupant: Occupant,
+    pub ready: bool,
+    /// Whether the seat has a live connection, which only humans have.
+    pub connected: bool,
+    /// The bear picked for this seat, if any.
+    pub bear: Option<BearCard>,
+}
+
+/// Who sits in a seat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Occupant {
+    Empty,
+    Human,
+    Bot,
+}
+
+/// Hosts one game.
+pub struct Server {
+    listener: TcpListener,
+    table: GameTable,
+    seats: Vec<Seat>,
+    writers: HashMap<usize, TcpStream>,
+    spectators: Vec<(usize, EventStream)>,
+    /// The agent that plays the bot seats.
+    pub bot_strategy: ActionSelectionStrategy,
+}
+
+/// A connection to a `Server`.
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Seat {
+    occupant: Option<Occupant>,
+    connection: Option<usize>,
+    token: String,
+    ready: bool,
+    bear: Option<BearCard>,
+}
+
+enum Event {
+    Connected(usize, TcpStream),
+    Received(usize, ClientMessage),
+    Invalid(usize, String),
+    Disconnected(usize),
+}
+
+impl Server {
+    /// Listens on `addr` for a game of `num_players` players dealt from `seed`. Fails with
+    /// `InvalidInput` if the rules do not allow that many players. A solo automaton is seated
+    /// as a bot.
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        num_players: usize,
+        rules: GameRules,
+        seed: u64,
+    ) -> io::Result<Self> {
+        let table = GameTable::from_seed(
+            num_players,
+            rules,
+            InitialPlayerSelectionStrategy::Random,
+            QuestTileSelectionStrategy::Random,
+            seed,
+        )
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid number of players: {}", num_players),
+            )
+        })?;
+        let mut seats = vec![Seat::default(); table.players.len()];
+        for seat in seats.iter_mut().skip(num_players) {
+            seat.occupant = Some(Occupant::Bot);
+            seat.ready = true;
+        }
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            table,
+            seats,
+            writers: HashMap::new(),
+            spectators: Vec::new(),
+            bot_strategy: ActionSelectionStrategy::Greedy,
+        })
+    }
+
+    /// The address clients connect to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Hosts the game until it is done and returns the finished table. Connections are still
+    /// accepted once the game has started, to reconnect or spectate, but nothing is answered
+    /// after it is over.
+    pub fn run(mut self) -> io::Result<GameTable> {
+        let (events, inbox) = mpsc::channel();
+        let listener = self.listener.try_clone()?;
+        thread::spawn(move || accept(listener, events));
+
+        for event in inbox {
+            match event {
+                Event::Connected(connection, writer) => {
+                    self.writers.insert(connection, writer);
+                }
+                Event::Disconnected(connection) => {
+                    self.writers.remove(&connection);
+                    self.spectators.retain(|(c, _)| *c != connection);
+                    if let Some(seat) = self.seat_of(connection) {
+                        self.seats[seat].connection = None;
+                        self.lobby_changed();
+                    }
+                }
+                Event::Invalid(connection, message) => {
+                    self.send(connection, &ServerMessage::Error { message });
+                }
+                Event::Received(connection, message) => {
+                    if let Err(message) = self.handle(connection, message) {
+                        self.send(connection, &ServerMessage::Error { message });
+                    }
+                }
+            }
+            if matches!(self.table.state, GameState::Done { .. }) {
+                return Ok(self.table);
+            }
+        }
+        Err(io::Error::other(
+            "The listener stopped accepting connections",
+        ))
+    }
+
+    fn handle(&mut self, connection: usize, message: ClientMessage) -> Result<(), String> {
+        match message {
+            ClientMessage::Join { seat } => self.join(connection, seat),
+            ClientMessage::Reconnect { token } => self.reconnect(connection, &token),
+            ClientMessage::PickBear { bear } => {
+                let seat = self.lobby_seat_of(connection)?;
+                if bear.is_some()
+                    && self
+                        .seats
+                        .iter()
+                        .enumerate()
+                        .any(|(other, s)| other != seat && s.bear == bear)
+                {
+                    return Err("That bear is taken".to_string());
+                }
+                self.seats[seat].bear = bear;
+                self.lobby_changed();
+                Ok(())
+            }
+            ClientMessage::Ready { ready } => {
+                let seat = self.lobby_seat_of(connection)?;
+                self.seats[seat].ready = ready;
+                self.lobby_changed();
+                self.start_if_ready();
+                Ok(())
+            }
+            ClientMessage::FillWithBots => {
+                self.lobby_seat_of(connection)?;
+                for seat in self.seats.iter_mut().filter(|s| s.occupant.is_none()) {
+                    seat.occupant = Some(Occupant::Bot);
+                    seat.ready = true;
+                }
+                self.lobby_changed();
+                self.start_if_ready();
+                Ok(())
+            }
+            ClientMessage::Act { action } => {
+                let seat = self.seat_of(connection).ok_or("Join a seat first")?;
+                if self.table.current_player_index() != Some(seat) {
+                    return Err("It is not your turn".to_string());
+                }
+                if !self.table.apply_action(action) {
+                    return Err(format!("Illegal action: {:?}", action));
+                }
+                self.play_bots();
+                self.broadcast_table();
+                Ok(())
+            }
+            ClientMessage::Spectate { delay } => {
+                if self.seat_of(connection).is_some() || self.is_spectating(connection) {
+                    return Err("Already at the table".to_string());
+                }
+                self.spectators
+                    .push((connection, EventStream::new(Audience::Spectator, delay)));
+                self.release_events();
+                Ok(())
+            }
+        }
+    }
+
+    fn join(&mut self, connection: usize, seat: Option<usize>) -> Result<(), String> {
+        if self.seat_of(connection).is_some() {
+            return Err("Already seated".to_string());
+        }
+        if self.is_spectating(connection) {
+            return Err("Spectators cannot take a seat".to_string());
+        }
+        if !matches!(self.table.state, GameState::Uninitialized) {
+            return Err("The game has started, reconnect to your seat instead".to_string());
+        }
+        let seat = match seat {
+            Some(seat) if self.seats.get(seat).is_some_and(|s| s.occupant.is_none()) => seat,
+            Some(seat) => return Err(format!("Seat {} is not available", seat)),
+            None => self
+                .seats
+                .iter()
+                .position(|s| s.occupant.is_none())
+                .ok_or("The table is full")?,
+        };
+        let token = format!("{:016x}", rand::random::<u64>());
+        self.seats[seat] = Seat {
+            occupant: Some(Occupant::Human),
+            connection: Some(connection),
+            token: token.clone(),
+            ready: false,
+            bear: None,
+        };
+        self.send(
+            connection,
+            &ServerMessage::Joined {
+                player_index: seat,
+                token,
+            },
+        );
+        self.lobby_changed();
+        Ok(())
+    }
+
+    fn reconnect(&mut self, connection: usize, token: &str) -> Result<(), String> {
+        let seat = self
+            .seats
+            .iter()
+            .position(|s| s.occupant == Some(Occupant::Human) && s.token == token)
+            .ok_or("Unknown token")?;
+        if let Some(previous) = self.seats[seat].connection.replace(connection) {
+            self.writers.remove(&previous);
+        }
+        self.send(
+            connection,
+            &ServerMessage::Joined {
+                player_index: seat,
+                token: token.to_string(),
+            },
+        );
+        match self.table.state {
+            GameState::Uninitialized => self.lobby_changed(),
+            _ => {
+                let view = Box::new(self.table.view_for(seat));
+                self.send(connection, &ServerMessage::View { view });
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts the game once every seat is taken and every human is ready.
+    fn start_if_ready(&mut self) {
+        let ready = self.seats.iter().all(|s| s.occupant.is_some() && s.ready);
+        if !ready || !matches!(self.table.state, GameState::Uninitialized) {
+            return;
+        }
+        let mut unpicked: Vec<BearCard> = BearCard::all()
+            .into_iter()
+            .filter(|b| !self.seats.iter().any(|s| s.bear == Some(*b)))
+            .collect();
+        unpicked.shuffle(&mut self.table.rng);
+        for (player_index, seat) in self.seats.iter_mut().enumerate() {
+            let bear = seat.bear.or_else(|| unpicked.pop());
+            seat.bear = bear;
+            let player = &mut self.table.players[player_index];
+            player.bear = bear.expect("There is a bear for every seat");
+            if seat.occupant == Some(Occupant::Bot) {
+                player.strategy = self.bot_strategy.clone();
+            }
+        }
+        self.table.play_one_step();
+        self.play_bots();
+        self.broadcast_table();
+    }
+
+    fn play_bots(&mut self) {
+        while let Some(player_index) = self.table.current_player_index() {
+            if self.seats[player_index].occupant != Some(Occupant::Bot) {
+                break;
+            }
+            self.table.play_one_step();
+        }
+    }
+
+    fn seat_of(&self, connection: usize) -> Option<usize> {
+        self.seats
+            .iter()
+            .position(|s| s.connection == Some(connection))
+    }
+
+    fn is_spectating(&self, connection: usize) -> bool {
+        self.spectators.iter().any(|(c, _)| *c == connection)
+    }
+
+    fn lobby_seat_of(&self, connection: usize) -> Result<usize, String> {
+        let seat = self.seat_of(connection).ok_or("Join a seat first")?;
+        match self.table.state {
+            GameState::Uninitialized => Ok(seat),
+            _ => Err("The game has already started".to_string()),
+        }
+    }
+
+    fn lobby_changed(&mut self) {
+        let seats: Vec<SeatInfo> = self
+            .seats
+            .iter()
+            .map(|s| SeatInfo {
+                occupant: s.occupant.unwrap_or(Occupant::Empty),
+                ready: s.ready,
+                connected: s.connection.is_some(),
+                bear: s.bear,
+            })
+            .collect();
+        self.send_to_seated(&ServerMessage::Lobby { seats });
+    }
+
+    fn broadcast_table(&mut self) {
+        for seat in 0..self.seats.len() {
+            if let Some(connection) = self.seats[seat].connection {
+                let view = Box::new(self.table.view_for(seat));
+                self.send(connection, &ServerMessage::View { view });
+            }
+        }
+        self.release_events();
+        if let GameState::Done { winner_indices } = &self.table.state {
+            let mut scores = vec![0; self.table.players.len()];
+            for player in &self.table.players {
+                scores[player.index] = player.score();
+            }
+            let done = ServerMessage::Done {
+                winner_indices: winner_indices.clone(),
+                scores,
+            };
+            self.send_to_seated(&done);
+            let spectators: Vec<usize> = self.spectators.iter().map(|(c, _)| *c).collect();
+            for connection in spectators {
+                self.send(connection, &done);
+            }
+        }
+    }
+
+    fn release_events(&mut self) {
+        let mut released = Vec::new();
+        for (connection, stream) in self.spectators.iter_mut() {
+            let events = stream.poll(&self.table.events);
+            if !events.is_empty() {
+                released.push((*connection, events));
+            }
+        }
+        for (connection, events) in released {
+            self.send(connection, &ServerMessage::Events { events });
+        }
+    }
+
+    fn send_to_seated(&mut self, message: &ServerMessage) {
+        let connections: Vec<usize> = self.seats.iter().filter_map(|s| s.connection).collect();
+        for connection in connections {
+            self.send(connection, message);
+        }
+    }
+
+    /// Sends `message` to `connection`, forgetting connections that can no longer be written
+    /// to.
+    fn send(&mut self, connection: usize, message: &ServerMessage) {
+        if let Some(writer) = self.writers.get_mut(&connection) {
+            if write_line(writer, message).is_err() {
+                self.writers.remove(&connection);
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Connects to the server at `addr`.
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let writer = TcpStream::connect(addr)?;
+        Ok(Self {
+            reader: BufReader::new(writer.try_clone()?),
+            writer,
+        })
+    }
+
+    /// Sends `message` to the server.
+    pub fn send(&mut self, message: &ClientMessage) -> io::Result<()> {
+        write_line(&mut self.writer, message)
+    }
+
+    /// Waits for the next message from the server.
+    pub fn recv(&mut self) -> io::Result<ServerMessage> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        serde_json::from_str(&line)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+fn accept(listener: TcpListener, events: Sender<Event>) {
+    for (connection, stream) in listener.incoming().enumerate() {
+        let Ok(stream) = stream else { continue };
+        let Ok(writer) = stream.try_clone() else {
+            continue;
+        };
+        if events.send(Event::Connected(connection, writer)).is_err() {
+            return;
+        }
+        let events = events.clone();
+        thread::spawn(move || {
+            for line in BufReader::new(stream).lines() {
+                let Ok(line) = line else { break };
+                let event = match serde_json::from_str(&line) {
+                    Ok(message) => Event::Received(connection, message),
+                    Err(error) => Event::Invalid(connection, format!("Invalid message: {}", error)),
+                };
+                if events.send(event).is_err() {
+                    return;
+                }
+            }
+            let _ = events.send(Event::Disconnected(connection));
+        });
+    }
+}
+
+fn write_line(writer: &mut TcpStream, message: &impl Serialize) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())
+}
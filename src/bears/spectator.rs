@@ -0,0 +1,185 @@
+//! Watching games from outside the table, live or from a recording.
+//!
+//! Every event is tagged with a `Visibility`. Spectators only receive public events and learn
+//! that private ones happened without their contents, optionally some turns late so they cannot
+//! feed information to a player. Casters see everything, including the decks and every hand.
+
+use super::system::{GameState, GameTable, LoggedEvents, Visibility};
+use super::view::CasterView;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Who is watching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "as", rename_all = "snake_case")]
+pub enum Audience {
+    Spectator,
+    /// Watching over the shoulder of the player with `player_index`.
+    Player {
+        player_index: usize,
+    },
+    Caster,
+}
+
+/// An event as an audience receives it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SpectatorEvent {
+    Revealed {
+        event: LoggedEvents,
+    },
+    /// Something only the player with `player_index` saw.
+    Hidden {
+        player_index: usize,
+    },
+}
+
+/// Releases the events of a game to one audience, holding back the last `delay` turns until
+/// the game is done.
+#[derive(Debug, Clone)]
+pub struct EventStream {
+    pub audience: Audience,
+    /// Turns held back while the game is running.
+    pub delay: usize,
+    released: usize,
+}
+
+/// A finished game with the whole table after every step, for replays.
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    pub frames: Vec<Frame>,
+}
+
+/// The events logged by one step and the table they left behind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Frame {
+    pub events: Vec<LoggedEvents>,
+    pub table: CasterView,
+}
+
+impl Audience {
+    /// Whether the audience may see the contents of an event with `visibility`.
+    pub fn sees(&self, visibility: Visibility) -> bool {
+        match (self, visibility) {
+            (Audience::Caster, _) | (_, Visibility::Public) => true,
+            (
+                Audience::Player { player_index },
+                Visibility::Private {
+                    player_index: owner,
+                },
+            ) => *player_index == owner,
+            _ => false,
+        }
+    }
+}
+
+impl SpectatorEvent {
+    /// `event` as `audience` receives it, or `None` if they do not even learn it happened.
+    pub fn of(event: &LoggedEvents, audience: Audience) -> Option<Self> {
+        let visibility = event.visibility();
+        match visibility {
+            _ if audience.sees(visibility) => Some(SpectatorEvent::Revealed {
+                event: event.clone(),
+            }),
+            Visibility::Private { player_index } => Some(SpectatorEvent::Hidden { player_index }),
+            _ => None,
+        }
+    }
+}
+
+impl EventStream {
+    /// A stream that has released nothing yet.
+    pub fn new(audience: Audience, delay: usize) -> Self {
+        Self {
+            audience,
+            delay,
+            released: 0,
+        }
+    }
+
+    /// The events of `log` that were held back or not yet released. `log` is the full event log
+    /// of the game being watched, which only ever grows.
+    pub fn poll(&mut self, log: &[LoggedEvents]) -> Vec<SpectatorEvent> {
+        let until = releasable(log, self.delay).max(self.released);
+        let events = log[self.released..until]
+            .iter()
+            .filter_map(|event| SpectatorEvent::of(event, self.audience))
+            .collect();
+        self.released = until;
+        events
+    }
+}
+
+/// How many events of `log` may be shown when the last `delay` turns are held back.
+fn releasable(log: &[LoggedEvents], delay: usize) -> usize {
+    if delay == 0 || matches!(log.last(), Some(LoggedEvents::Finished { .. })) {
+        return log.len();
+    }
+    let turns: Vec<usize> = log
+        .iter()
+        .enumerate()
+        .filter(|(_, event)| {
+            matches!(
+                event,
+                LoggedEvents::DrewResource { .. }
+                    | LoggedEvents::CompletedQuest { .. }
+                    | LoggedEvents::Passed { .. }
+            )
+        })
+        .map(|(i, _)| i)
+        .collect();
+    match turns.len().checked_sub(delay) {
+        Some(kept) => turns[kept],
+        None => turns.first().copied().unwrap_or(log.len()),
+    }
+}
+
+impl Recording {
+    /// Plays `table` until it is done, keeping a frame for the setup and for every turn.
+    pub fn record(table: &mut GameTable) -> Self {
+        let mut recording = Self::default();
+        let mut logged = 0;
+        loop {
+            if table.events.len() > logged {
+                recording.frames.push(Frame {
+                    events: table.events[logged..].to_vec(),
+                    table: table.caster_view(),
+                });
+                logged = table.events.len();
+            }
+            if matches!(table.state, GameState::Done { .. }) {
+                return recording;
+            }
+            table.play_one_step();
+        }
+    }
+
+    /// Every event of the game, in order.
+    pub fn events(&self) -> impl Iterator<Item = &LoggedEvents> {
+        self.frames.iter().flat_map(|f| f.events.iter())
+    }
+
+    /// Writes the recording as JSON, one frame per line.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        for frame in &self.frames {
+            serde_json::to_writer(&mut writer, frame)?;
+            writeln!(writer)?;
+        }
+        writer.flush()
+    }
+
+    /// Reads a recording written by `save`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut frames = Vec::new();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                frames.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(Self { frames })
+    }
+}
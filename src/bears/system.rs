@@ -1,115 +1,583 @@
+//! Running a table: players, turns, actions and the event log.
+//!
+//! The printed rules only cover the cards and the setup, which is all this crate started with.
+//! The turn rules here are the simulator's own, chosen to be simple enough to search:
+//!
+//! - On their turn a player draws a resource, completes one of their quest tiles or an open
+//!   quest they can pay for, or passes when they can do none of these.
+//! - `GameRules::open_quests` quests are open at a time, three in the standard game, and a
+//!   completed open quest is replaced from the quest deck. An empty resource deck is refilled
+//!   by shuffling the discard pile.
+//! - The game ends when every player has passed in a row, when no quests are left, or at the
+//!   turn limit of the rules. The highest score wins.
+//! - Fortune cards are drawn and discarded like any other card, but their effects are never
+//!   resolved.
+
+use super::mcts::{self, SearchBudget};
 use super::models::*;
+use super::rules::{GameRules, SoloOpponent};
+use super::view::{CasterPlayer, CasterView, PlayerView, PublicPlayer};
 use crate::utils::Deck;
-use rand::{seq::SliceRandom, Rng};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+/// A seat at the table, with everything that player holds and the agent that plays it.
+#[derive(Debug, Clone)]
 pub struct Player {
+    /// Names the player for the whole game, whichever seat they end up in.
     pub index: usize,
     pub bear: BearCard,
+    /// The cards in the order they were drawn. Hands stay a `Vec` rather than `ResourceCounts`
+    /// for now, because views, recordings and both bindings expose them as lists of cards.
     pub hand: Vec<ResourceCard>,
+    /// Quests only this player may complete, face down to everyone else.
     pub quest_tiles: Vec<QuestTile>,
+    /// Quests the player completed, which score their reward.
     pub completed_quests: Vec<QuestCard>,
+    /// The quest tiles among `completed_quests`. Every other completed quest was an open quest.
+    pub completed_quest_tiles: Vec<QuestTile>,
+    /// The agent that plays this seat when the table plays itself.
+    pub strategy: ActionSelectionStrategy,
 }
 
-#[derive(Debug, Clone)]
+/// Where the table is in the game. Players are named by their `index`, not their seat.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "phase", rename_all = "snake_case")]
 pub enum GameState {
+    /// Nothing has been dealt yet.
     Uninitialized,
+    /// Waiting for the player with `player_index` to act.
     PlayerReady { player_index: usize },
+    /// The game is over. Everyone in `winner_indices` shares the win, which nobody gets when a
+    /// solo player misses their score target.
     Done { winner_indices: Vec<usize> },
 }
 
-#[allow(dead_code)]
-#[derive(Debug)]
+/// Who takes the first turn once the table is set up.
+#[derive(Debug, Clone)]
 pub enum InitialPlayerSelectionStrategy {
     Random,
+    /// The player with index 0.
     First,
+    /// The player with the highest index.
     Last,
 }
 
-#[allow(dead_code)]
-#[derive(Debug)]
+/// How quest tiles are handed out during setup. Tiles are dealt one at a time around the table,
+/// after the starting resources.
+#[derive(Debug, Clone)]
 pub enum QuestTileSelectionStrategy {
+    /// Every player gets random tiles.
     Random,
+    /// Every player gets the remaining tile whose reward is closest to an even share of what
+    /// they still need for `target`, so their last tile brings them as close to `target` as the
+    /// remaining tiles allow.
     Target { target: usize },
+    /// Every player picks the remaining tile their hand is closest to paying for, preferring
+    /// higher rewards.
+    Greedy,
+}
+
+/// The agent that picks a player's actions.
+#[derive(Debug, Clone)]
+pub enum ActionSelectionStrategy {
+    /// Any legal action, uniformly at random.
+    Random,
+    /// The most rewarding quest it can complete, or else a draw. See `greedy_choice`.
     Greedy,
+    /// Monte Carlo Tree Search within `budget`. See `mcts::search`.
+    Mcts { budget: SearchBudget },
 }
 
-#[derive(Debug)]
+/// Everything a player can do on their turn. Indices refer to the player's quest tiles and to
+/// the open quests at the time the action is taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Action {
+    /// Takes the top card of the resource deck, reshuffling the discard pile if it is empty.
+    DrawResource,
+    /// Pays for and completes one of the player's own quest tiles.
+    CompleteQuestTile { tile_index: usize },
+    /// Pays for and completes an open quest, which is replaced from the quest deck.
+    CompleteOpenQuest { quest_index: usize },
+    /// Only legal when nothing else is.
+    Pass,
+}
+
+/// Everything that happened at the table, in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
 pub enum LoggedEvents {
-    BuiltQuestDeck { quests: Deck<QuestCard> },
-    BuiltResourceDeck { resources: Deck<ResourceCard> },
-    SelectedInitialPlayer { player_index: usize },
+    AppliedRules {
+        rules: GameRules,
+    },
+    BuiltQuestDeck {
+        quests: Deck<QuestCard>,
+    },
+    BuiltResourceDeck {
+        resources: Deck<ResourceCard>,
+    },
+    SelectedInitialPlayer {
+        player_index: usize,
+    },
+    DrewResource {
+        player_index: usize,
+        resource: ResourceCard,
+    },
+    CompletedQuest {
+        player_index: usize,
+        quest: QuestCard,
+    },
+    Passed {
+        player_index: usize,
+    },
+    ExpiredQuest {
+        quest: QuestCard,
+    },
+    Finished {
+        winner_indices: Vec<usize>,
+    },
+}
+
+/// Who gets to see an event as it happens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "to", rename_all = "snake_case")]
+pub enum Visibility {
+    Public,
+    /// Only the player with `player_index` sees it; everyone else only learns that it happened.
+    Private {
+        player_index: usize,
+    },
+    /// Nobody at the table sees it, only replays do.
+    Omniscient,
+}
+
+impl LoggedEvents {
+    /// Who sees this event as it happens.
+    pub fn visibility(&self) -> Visibility {
+        match self {
+            LoggedEvents::BuiltQuestDeck { .. } | LoggedEvents::BuiltResourceDeck { .. } => {
+                Visibility::Omniscient
+            }
+            LoggedEvents::DrewResource { player_index, .. } => Visibility::Private {
+                player_index: *player_index,
+            },
+            LoggedEvents::AppliedRules { .. }
+            | LoggedEvents::SelectedInitialPlayer { .. }
+            | LoggedEvents::CompletedQuest { .. }
+            | LoggedEvents::Passed { .. }
+            | LoggedEvents::ExpiredQuest { .. }
+            | LoggedEvents::Finished { .. } => Visibility::Public,
+        }
+    }
 }
 
-#[derive(Debug)]
+/// Resource cards that are unaccounted for, or that exist more often than they should.
+#[derive(Debug, Clone)]
+pub struct CardConservationError {
+    /// Cards of the full deck found nowhere on the table.
+    pub missing: ResourceCounts,
+    /// Cards on the table beyond those of the full deck.
+    pub extra: ResourceCounts,
+}
+
+/// A game from setup to the final score. Create one with `from` or `from_seed`, then drive it
+/// with `play_one_step`, `play_until_done` or `apply_action`.
+#[derive(Debug, Clone)]
 pub struct GameTable {
+    pub rules: GameRules,
+    /// Every player in turn order, starting with whoever went first.
     pub players: Vec<Player>,
+    /// The quest deck, from which open quests are revealed.
     pub quests: Deck<QuestCard>,
+    /// Quest tiles that were not dealt to anyone.
     pub quest_tiles: Deck<QuestTile>,
     pub resources: Deck<ResourceCard>,
+    /// Quests anyone may complete, in the order they were revealed.
     pub open_quests: Vec<QuestCard>,
+    /// Cards spent on quests, shuffled back into `resources` once it runs out.
     pub discard_pile: Deck<ResourceCard>,
+    /// Open quests that ran out of time in a solo game against a score target.
+    pub expired_quests: Vec<QuestCard>,
     pub state: GameState,
     pub initial_player_selection_strategy: InitialPlayerSelectionStrategy,
     pub quest_tile_selection_strategy: QuestTileSelectionStrategy,
+    /// Passes in a row so far. The game ends once every player has passed.
+    pub consecutive_passes: usize,
+    pub turns_played: usize,
+    /// Everything that happened at the table, in order.
     pub events: Vec<LoggedEvents>,
+    /// Every shuffle of the decks, and the random choices of setup.
+    pub rng: StdRng,
+    /// Random choices of the agents, kept apart from `rng` so that the deals and reshuffles of a
+    /// seed are the same whichever agents play it.
+    pub agent_rng: StdRng,
+    /// Verify card conservation after setup and every action. On by default in debug builds.
+    pub check_invariants: bool,
 }
 
 impl GameTable {
+    /// A table for `num_players` players, or `None` if the rules are invalid or do not allow
+    /// that many players.
     pub fn from(
         num_players: usize,
+        rules: GameRules,
+        initial_player_selection_strategy: InitialPlayerSelectionStrategy,
+        quest_tile_selection_strategy: QuestTileSelectionStrategy,
+    ) -> Option<Self> {
+        Self::from_seed(
+            num_players,
+            rules,
+            initial_player_selection_strategy,
+            quest_tile_selection_strategy,
+            rand::random(),
+        )
+    }
+
+    /// Like `from`, but every shuffle and random choice of the game, including those of the
+    /// agents, is derived from `seed`.
+    pub fn from_seed(
+        num_players: usize,
+        rules: GameRules,
         initial_player_selection_strategy: InitialPlayerSelectionStrategy,
         quest_tile_selection_strategy: QuestTileSelectionStrategy,
+        seed: u64,
     ) -> Option<Self> {
-        if num_players < 2 || num_players > 6 {
+        if rules.validate().is_err() || !rules.allows(num_players) {
             return None;
         }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let agent_rng = StdRng::seed_from_u64(rng.gen());
+        let mut players = Self::create_players(rules.seats(num_players), &mut rng);
+        if players.len() > num_players {
+            players[1].strategy = ActionSelectionStrategy::Greedy;
+        }
         Some(Self {
-            players: Self::create_players(num_players),
+            rules,
+            players,
             quests: Deck::new(),
             quest_tiles: Deck::new(),
             resources: Deck::new(),
             open_quests: Vec::new(),
             discard_pile: Deck::new(),
+            expired_quests: Vec::new(),
             state: GameState::Uninitialized,
             initial_player_selection_strategy,
             quest_tile_selection_strategy,
+            consecutive_passes: 0,
+            turns_played: 0,
             events: Vec::new(),
+            rng,
+            agent_rng,
+            check_invariants: cfg!(debug_assertions),
         })
     }
 
+    /// Sets the table up, or lets the current player's agent take one turn.
     pub fn play_one_step(&mut self) {
         match self.state {
             GameState::Uninitialized => {
+                self.add_event(LoggedEvents::AppliedRules {
+                    rules: self.rules.clone(),
+                });
                 self.build_quest_deck();
                 self.build_resource_deck();
                 self.select_initial_player();
                 self.distribute_resources();
                 self.distribute_quest_tiles();
+                self.reveal_open_quests();
+                // A turn limit of zero ends the game before anyone plays.
+                self.finish_if_over();
+                self.enforce_invariants();
             }
             GameState::PlayerReady { player_index } => {
-                self.state = GameState::Done {
-                    winner_indices: vec![player_index],
-                };
+                let action = self.select_action(player_index);
+                self.apply_action(action);
             }
             GameState::Done { .. } => {}
         }
     }
 
+    /// Checks that the resource deck, the discard pile and the players' hands together hold
+    /// exactly the cards of `build_all_resources`. Nothing is dealt before the game is set up,
+    /// so an uninitialized table always passes.
+    pub fn check_card_conservation(&self) -> Result<(), CardConservationError> {
+        if let GameState::Uninitialized = self.state {
+            return Ok(());
+        }
+        let expected: ResourceCounts = build_all_resources().into_iter().collect();
+        let actual: ResourceCounts = self
+            .resources
+            .cards
+            .iter()
+            .chain(self.discard_pile.cards.iter())
+            .chain(self.players.iter().flat_map(|p| p.hand.iter()))
+            .copied()
+            .collect();
+        let missing = expected.difference(&actual);
+        let extra = actual.difference(&expected);
+        if missing.is_empty() && extra.is_empty() {
+            Ok(())
+        } else {
+            Err(CardConservationError { missing, extra })
+        }
+    }
+
+    /// Plays every remaining turn with the players' agents.
+    pub fn play_until_done(&mut self) {
+        while !matches!(self.state, GameState::Done { .. }) {
+            self.play_one_step();
+        }
+    }
+
+    /// The player whose turn it is, or `None` before setup and after the game.
+    pub fn current_player_index(&self) -> Option<usize> {
+        match self.state {
+            GameState::PlayerReady { player_index } => Some(player_index),
+            _ => None,
+        }
+    }
+
+    /// The player with index `player_index`, wherever they sit.
+    pub fn player(&self, player_index: usize) -> &Player {
+        self.players
+            .iter()
+            .find(|p| p.index == player_index)
+            .expect("No player with this index")
+    }
+
+    /// What the player with index `player_index` can see of the table.
+    pub fn view_for(&self, player_index: usize) -> PlayerView {
+        let player = self.player(player_index);
+        PlayerView {
+            rules: self.rules.clone(),
+            player_index,
+            hand: player.hand.clone(),
+            quest_tiles: player.quest_tiles.clone(),
+            players: self.players.iter().map(PublicPlayer::of).collect(),
+            open_quests: self.open_quests.clone(),
+            discard_pile: self.discard_pile.clone(),
+            expired_quests: self.expired_quests.clone(),
+            quests_remaining: self.quests.len(),
+            resources_remaining: self.resources.len(),
+            quest_tiles_remaining: self.quest_tiles.len(),
+            consecutive_passes: self.consecutive_passes,
+            turns_played: self.turns_played,
+            state: self.state.clone(),
+            legal_actions: match self.current_player_index() {
+                Some(current) if current == player_index => self.legal_actions(),
+                _ => Vec::new(),
+            },
+        }
+    }
+
+    /// The whole table with nothing hidden.
+    pub fn caster_view(&self) -> CasterView {
+        CasterView {
+            rules: self.rules.clone(),
+            players: self.players.iter().map(CasterPlayer::of).collect(),
+            open_quests: self.open_quests.clone(),
+            quests: self.quests.clone(),
+            quest_tiles: self.quest_tiles.clone(),
+            resources: self.resources.clone(),
+            discard_pile: self.discard_pile.clone(),
+            expired_quests: self.expired_quests.clone(),
+            consecutive_passes: self.consecutive_passes,
+            turns_played: self.turns_played,
+            state: self.state.clone(),
+        }
+    }
+
+    /// Actions the current player may take. Passing is only allowed when nothing else is.
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let player = match self.current_player_index() {
+            Some(player_index) => self.player(player_index),
+            None => return Vec::new(),
+        };
+        let hand: CompactHand = player.hand.iter().copied().collect();
+        let mut actions = Vec::new();
+        if !self.resources.is_empty() || !self.discard_pile.is_empty() {
+            actions.push(Action::DrawResource);
+        }
+        for (tile_index, tile) in player.quest_tiles.iter().enumerate() {
+            if hand.covers(&tile.quest.compact_cost()) {
+                actions.push(Action::CompleteQuestTile { tile_index });
+            }
+        }
+        for (quest_index, quest) in self.open_quests.iter().enumerate() {
+            if hand.covers(&quest.compact_cost()) {
+                actions.push(Action::CompleteOpenQuest { quest_index });
+            }
+        }
+        if actions.is_empty() {
+            actions.push(Action::Pass);
+        }
+        actions
+    }
+
+    /// Plays `action` for the current player and passes the turn. Returns `false`, leaving the
+    /// table untouched, if the action is not legal.
+    pub fn apply_action(&mut self, action: Action) -> bool {
+        let player_index = match self.current_player_index() {
+            Some(player_index) => player_index,
+            None => return false,
+        };
+        if !self.legal_actions().contains(&action) {
+            return false;
+        }
+        let seat = self.seat_of(player_index);
+        match action {
+            Action::DrawResource => {
+                if self.resources.is_empty() {
+                    self.resources = std::mem::take(&mut self.discard_pile);
+                    self.resources.shuffle_with(&mut self.rng);
+                }
+                let resource = self
+                    .resources
+                    .draw()
+                    .expect("Drawing is only legal when there are resources left");
+                self.players[seat].add_to_hand(resource);
+                self.add_event(LoggedEvents::DrewResource {
+                    player_index,
+                    resource,
+                });
+                if self.is_solo_against_target() {
+                    self.expire_oldest_open_quest();
+                }
+            }
+            Action::CompleteQuestTile { tile_index } => {
+                let tile = self.players[seat].quest_tiles.remove(tile_index);
+                self.players[seat].completed_quest_tiles.push(tile);
+                self.complete_quest(seat, tile.quest);
+            }
+            Action::CompleteOpenQuest { quest_index } => {
+                let quest = self.open_quests.remove(quest_index);
+                if let Some(quest) = self.quests.draw() {
+                    self.open_quests.push(quest);
+                }
+                self.complete_quest(seat, quest);
+            }
+            Action::Pass => {
+                self.add_event(LoggedEvents::Passed { player_index });
+            }
+        }
+        self.consecutive_passes = match action {
+            Action::Pass => self.consecutive_passes + 1,
+            _ => 0,
+        };
+        self.turns_played += 1;
+        self.advance_turn(seat);
+        self.enforce_invariants();
+        true
+    }
+
+    /// Panics if `check_invariants` is set and a card went missing or was duplicated.
+    fn enforce_invariants(&self) {
+        if self.check_invariants {
+            if let Err(error) = self.check_card_conservation() {
+                panic!("{} after {:?}", error, self.events.last());
+            }
+        }
+    }
+
+    fn select_action(&mut self, player_index: usize) -> Action {
+        let view = self.view_for(player_index);
+        match self.player(player_index).strategy.clone() {
+            ActionSelectionStrategy::Random => *view
+                .legal_actions
+                .choose(&mut self.agent_rng)
+                .expect("There is always a legal action"),
+            ActionSelectionStrategy::Greedy => view.greedy_action(),
+            ActionSelectionStrategy::Mcts { budget } => {
+                mcts::search(&view, &budget, &mut self.agent_rng)
+            }
+        }
+    }
+
+    fn seat_of(&self, player_index: usize) -> usize {
+        self.players
+            .iter()
+            .position(|p| p.index == player_index)
+            .expect("No player with this index")
+    }
+
+    fn complete_quest(&mut self, seat: usize, quest: QuestCard) {
+        let player = &mut self.players[seat];
+        let mut payment = quest
+            .payment_from(&player.hand)
+            .expect("Completing a quest is only legal when it is affordable");
+        payment.sort_unstable_by(|a, b| b.cmp(a));
+        for i in payment {
+            self.discard_pile.place(player.hand.swap_remove(i));
+        }
+        player.completed_quests.push(quest);
+        let player_index = player.index;
+        self.add_event(LoggedEvents::CompletedQuest {
+            player_index,
+            quest,
+        });
+    }
+
+    fn is_solo_against_target(&self) -> bool {
+        self.players.len() == 1 && matches!(self.rules.solo_opponent, SoloOpponent::ScoreTarget(_))
+    }
+
+    /// A lone player racing a score target has no opponent to take quests away, so instead the
+    /// oldest open quest leaves the table every time they draw.
+    fn expire_oldest_open_quest(&mut self) {
+        if self.open_quests.is_empty() {
+            return;
+        }
+        let quest = self.open_quests.remove(0);
+        if let Some(next) = self.quests.draw() {
+            self.open_quests.push(next);
+        }
+        self.expired_quests.push(quest);
+        self.add_event(LoggedEvents::ExpiredQuest { quest });
+    }
+
+    fn advance_turn(&mut self, seat: usize) {
+        if !self.finish_if_over() {
+            let player_index = self.players[(seat + 1) % self.players.len()].index;
+            self.state = GameState::PlayerReady { player_index };
+        }
+    }
+
+    /// Ends the game if it is over, and tells whether it did.
+    fn finish_if_over(&mut self) -> bool {
+        let no_quests_left =
+            self.open_quests.is_empty() && self.players.iter().all(|p| p.quest_tiles.is_empty());
+        let out_of_turns = self
+            .rules
+            .turn_limit
+            .is_some_and(|limit| self.turns_played >= limit);
+        if !(no_quests_left || out_of_turns || self.consecutive_passes >= self.players.len()) {
+            return false;
+        }
+        let best = match (self.players.len(), self.rules.solo_opponent) {
+            (1, SoloOpponent::ScoreTarget(target)) => target,
+            _ => self.players.iter().map(|p| p.score()).max().unwrap_or(0),
+        };
+        let winner_indices: Vec<usize> = self
+            .players
+            .iter()
+            .filter(|p| p.score() >= best)
+            .map(|p| p.index)
+            .collect();
+        self.state = GameState::Done {
+            winner_indices: winner_indices.clone(),
+        };
+        self.add_event(LoggedEvents::Finished { winner_indices });
+        true
+    }
+
     fn add_event(&mut self, event: LoggedEvents) {
         self.events.push(event);
     }
 
-    fn create_players(num_players: usize) -> Vec<Player> {
-        let mut bears = vec![
-            BearCard::Polar,
-            BearCard::Panda,
-            BearCard::Sloth,
-            BearCard::Spectacled,
-            BearCard::Grizzly,
-            BearCard::Sun,
-        ];
-        bears.shuffle(&mut rand::thread_rng());
+    fn create_players(num_players: usize, rng: &mut StdRng) -> Vec<Player> {
+        let mut bears = BearCard::all();
+        bears.shuffle(rng);
         bears
             .iter()
             .enumerate()
@@ -121,12 +589,11 @@ impl GameTable {
     fn build_quest_deck(&mut self) {
         let (quests, quest_tiles) = build_all_quests();
         self.quests = Deck::from(quests);
-        self.quests.shuffle();
-        let num_quests = match self.players.len() {
-            1..=3 => 12,
-            4..=5 => 15,
-            _ => self.quests.len(),
-        };
+        self.quests.shuffle_with(&mut self.rng);
+        let num_quests = self
+            .rules
+            .quest_deck_size(self.players.len())
+            .expect("Validated rules size the quest deck for every table");
         self.quests.truncate(num_quests);
         self.quest_tiles = Deck::from(quest_tiles);
         self.add_event(LoggedEvents::BuiltQuestDeck {
@@ -136,59 +603,79 @@ impl GameTable {
 
     fn build_resource_deck(&mut self) {
         self.resources = Deck::from(build_all_resources());
-        self.resources.shuffle();
+        self.resources.shuffle_with(&mut self.rng);
         self.add_event(LoggedEvents::BuiltResourceDeck {
             resources: self.resources.clone(),
         });
     }
 
     fn distribute_resources(&mut self) {
-        let initial_resource_count = 5;
-        for _ in 0..initial_resource_count {
-            for player in &mut self.players {
-                match self.resources.draw() {
-                    Some(resource) => player.add_to_hand(resource),
-                    None => {
-                        panic!("Ran out of resources while distributing. This should never happen.")
-                    }
-                }
+        let hands = match self
+            .resources
+            .deal(self.players.len(), self.rules.starting_resources)
+        {
+            Some(hands) => hands,
+            None => panic!("Ran out of resources while distributing. This should never happen."),
+        };
+        for (player, hand) in self.players.iter_mut().zip(hands) {
+            for resource in hand {
+                player.add_to_hand(resource);
             }
         }
     }
 
     fn distribute_quest_tiles(&mut self) {
-        let tiles_per_player = 3;
-        match self.quest_tile_selection_strategy {
-            QuestTileSelectionStrategy::Random => {
-                for _ in 0..tiles_per_player {
-                    for player in &mut self.players {
-                        let quest_tile_index =
-                            rand::thread_rng().gen_range(0..self.quest_tiles.len());
-                        let quest_tile = self.quest_tiles.draw_from(quest_tile_index);
-                        match quest_tile {
-                            Some(quest_tile) => player.add_quest_tile(quest_tile),
-                            None => {
-                                panic!("Ran out of quest tiles while distributing. This should never happen.")
-                            }
-                        }
-                    }
+        if !matches!(
+            self.quest_tile_selection_strategy,
+            QuestTileSelectionStrategy::Random
+        ) {
+            // Ties between equally good tiles are broken by deck order.
+            self.quest_tiles.shuffle_with(&mut self.rng);
+        }
+        for round in 0..self.rules.quest_tiles_per_player {
+            for seat in 0..self.players.len() {
+                if self.quest_tiles.is_empty() {
+                    panic!("Ran out of quest tiles while distributing. This should never happen.")
                 }
-            }
-            QuestTileSelectionStrategy::Greedy => {
-                todo!("Greedy quest tile selection not implemented yet")
-            }
-
-            QuestTileSelectionStrategy::Target { target: _ } => {
-                todo!("Target quest tile selection not implemented yet")
+                let player = &self.players[seat];
+                let tiles = &self.quest_tiles.cards;
+                let quest_tile_index = match self.quest_tile_selection_strategy {
+                    QuestTileSelectionStrategy::Random => self.rng.gen_range(0..tiles.len()),
+                    QuestTileSelectionStrategy::Target { target } => {
+                        let dealt: usize =
+                            player.quest_tiles.iter().map(|t| t.quest.reward()).sum();
+                        let picks_left = self.rules.quest_tiles_per_player - round;
+                        let ideal = target.saturating_sub(dealt) / picks_left;
+                        (0..tiles.len())
+                            .min_by_key(|&i| tiles[i].quest.reward().abs_diff(ideal))
+                            .expect("The tile deck is not empty")
+                    }
+                    QuestTileSelectionStrategy::Greedy => (0..tiles.len())
+                        .min_by_key(|&i| {
+                            let quest = &tiles[i].quest;
+                            (
+                                quest.missing_from(&player.hand),
+                                std::cmp::Reverse(quest.reward()),
+                            )
+                        })
+                        .expect("The tile deck is not empty"),
+                };
+                let quest_tile = self
+                    .quest_tiles
+                    .draw_from(quest_tile_index)
+                    .expect("The index is within the deck");
+                self.players[seat].add_quest_tile(quest_tile);
             }
         }
     }
 
+    fn reveal_open_quests(&mut self) {
+        self.open_quests = self.quests.draw_n(self.rules.open_quests);
+    }
+
     fn select_initial_player(&mut self) {
         let mut player_index = match self.initial_player_selection_strategy {
-            InitialPlayerSelectionStrategy::Random => {
-                rand::thread_rng().gen_range(0..self.players.len())
-            }
+            InitialPlayerSelectionStrategy::Random => self.rng.gen_range(0..self.players.len()),
             InitialPlayerSelectionStrategy::First => 0,
             InitialPlayerSelectionStrategy::Last => self.players.len() - 1,
         };
@@ -199,7 +686,37 @@ impl GameTable {
     }
 }
 
+impl std::fmt::Display for CardConservationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Resource cards are not conserved: missing {:?}, extra {:?}",
+            self.missing.cards().collect::<Vec<_>>(),
+            self.extra.cards().collect::<Vec<_>>()
+        )
+    }
+}
+
+/// Completes the most rewarding affordable quest, otherwise draws.
+pub fn greedy_choice(
+    quest_tiles: &[QuestTile],
+    open_quests: &[QuestCard],
+    legal_actions: &[Action],
+) -> Action {
+    let reward = |action: &Action| match *action {
+        Action::CompleteQuestTile { tile_index } => quest_tiles[tile_index].quest.reward(),
+        Action::CompleteOpenQuest { quest_index } => open_quests[quest_index].reward(),
+        _ => 0,
+    };
+    legal_actions
+        .iter()
+        .max_by_key(|a| (reward(a), matches!(a, Action::DrawResource)))
+        .copied()
+        .unwrap_or(Action::Pass)
+}
+
 impl Player {
+    /// A player with empty hands, played by the random agent.
     pub fn new(index: usize, bear: BearCard) -> Self {
         Self {
             index,
@@ -207,14 +724,132 @@ impl Player {
             hand: Vec::new(),
             quest_tiles: Vec::new(),
             completed_quests: Vec::new(),
+            completed_quest_tiles: Vec::new(),
+            strategy: ActionSelectionStrategy::Random,
         }
     }
 
+    /// Sum of the rewards of the completed quests.
+    pub fn score(&self) -> usize {
+        super::models::score(&self.completed_quests)
+    }
+
+    /// Puts `resource` in the player's hand.
     pub fn add_to_hand(&mut self, resource: ResourceCard) {
         self.hand.push(resource);
     }
 
+    /// Deals `tile` to the player.
     pub fn add_quest_tile(&mut self, tile: QuestTile) {
         self.quest_tiles.push(tile);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> GameTable {
+        let mut table = GameTable::from_seed(
+            3,
+            GameRules::default(),
+            InitialPlayerSelectionStrategy::First,
+            QuestTileSelectionStrategy::Random,
+            4,
+        )
+        .unwrap();
+        table.play_one_step();
+        table
+    }
+
+    #[test]
+    fn last_target_tile_comes_closest_to_the_target() {
+        let target = 10;
+        for seed in 0..10 {
+            let mut table = GameTable::from_seed(
+                4,
+                GameRules::default(),
+                InitialPlayerSelectionStrategy::First,
+                QuestTileSelectionStrategy::Target { target },
+                seed,
+            )
+            .unwrap();
+            table.play_one_step();
+            let last_tiles: Vec<QuestTile> = table
+                .players
+                .iter()
+                .map(|p| *p.quest_tiles.last().unwrap())
+                .collect();
+            for (seat, player) in table.players.iter().enumerate() {
+                let total: usize = player.quest_tiles.iter().map(|t| t.quest.reward()).sum();
+                let before_last = total - last_tiles[seat].quest.reward();
+                // The tiles left when this player took their last one.
+                let mut remaining = table
+                    .quest_tiles
+                    .cards
+                    .iter()
+                    .chain(&last_tiles[seat + 1..]);
+                assert!(remaining.all(|tile| {
+                    total.abs_diff(target) <= (before_last + tile.quest.reward()).abs_diff(target)
+                }));
+            }
+        }
+    }
+
+    #[test]
+    fn greedy_tiles_start_with_the_closest_quest() {
+        for seed in 0..10 {
+            let mut table = GameTable::from_seed(
+                3,
+                GameRules::default(),
+                InitialPlayerSelectionStrategy::First,
+                QuestTileSelectionStrategy::Greedy,
+                seed,
+            )
+            .unwrap();
+            table.play_one_step();
+            let first = &table.players[0];
+            let closest = QuestId::all()
+                .map(|id| QuestCard { id }.missing_from(&first.hand))
+                .min()
+                .unwrap();
+            assert!(first
+                .quest_tiles
+                .iter()
+                .any(|t| t.quest.missing_from(&first.hand) == closest));
+            assert!(table
+                .players
+                .iter()
+                .all(|p| p.quest_tiles.len() == table.rules.quest_tiles_per_player));
+        }
+    }
+
+    #[test]
+    fn detects_a_duplicated_card() {
+        let mut table = table();
+        assert!(table.check_card_conservation().is_ok());
+        let card = table.players[1].hand[0];
+        table.players[1].hand.push(card);
+        let error = table.check_card_conservation().unwrap_err();
+        assert!(error.missing.is_empty());
+        assert_eq!(error.extra.cards().collect::<Vec<_>>(), vec![card]);
+    }
+
+    #[test]
+    fn detects_a_lost_card() {
+        let mut table = table();
+        let card = table.players[2].hand.pop().unwrap();
+        let error = table.check_card_conservation().unwrap_err();
+        assert_eq!(error.missing.cards().collect::<Vec<_>>(), vec![card]);
+        assert!(error.extra.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Resource cards are not conserved")]
+    fn apply_action_checks_conservation() {
+        let mut table = table();
+        table.check_invariants = true;
+        table.players[1].hand.clear();
+        table.apply_action(table.legal_actions()[0]);
+    }
+}
@@ -0,0 +1,458 @@
+//! Tournaments between agents, with Elo ratings and head to head records.
+
+use super::mcts::SearchBudget;
+use super::rules::GameRules;
+use super::system::{
+    ActionSelectionStrategy, GameState, GameTable, InitialPlayerSelectionStrategy,
+    QuestTileSelectionStrategy,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::fmt::Write;
+
+const INITIAL_RATING: f64 = 1500.0;
+const K_FACTOR: f64 = 24.0;
+const BOOTSTRAP_SAMPLES: usize = 200;
+
+/// A contestant: a strategy and the name it is reported under.
+#[derive(Debug, Clone)]
+pub struct Agent {
+    pub name: String,
+    pub strategy: ActionSelectionStrategy,
+}
+
+/// Outcome of one tournament game. Every vector is indexed by seat.
+#[derive(Debug, Clone)]
+pub struct GameResult {
+    /// The seed the game was dealt from.
+    pub seed: u64,
+    /// The agent in each seat, as an index into the tournament's agents.
+    pub lineup: Vec<usize>,
+    pub scores: Vec<usize>,
+    pub winner_seats: Vec<usize>,
+}
+
+/// How one agent did over the whole tournament.
+#[derive(Debug, Clone)]
+pub struct AgentStanding {
+    pub name: String,
+    /// Games the agent played in, counting a game once however many seats it took.
+    pub games: usize,
+    /// Games won, with shared wins split between the winners.
+    pub wins: f64,
+    /// Elo rating after the last game.
+    pub rating: f64,
+    /// Bounds of the 95% bootstrap interval of `rating`.
+    pub rating_low: f64,
+    pub rating_high: f64,
+}
+
+/// Everything a tournament measured. Agents are indexed as in the list the tournament was run
+/// with.
+#[derive(Debug, Clone)]
+pub struct TournamentReport {
+    pub standings: Vec<AgentStanding>,
+    /// `head_to_head[a][b]` is how often agent `a` outscored agent `b` when they shared a table,
+    /// with ties counting as half.
+    pub head_to_head: Vec<Vec<f64>>,
+    /// Number of games agents `a` and `b` shared a table in.
+    pub meetings: Vec<Vec<usize>>,
+    /// Every game, in the order they were played.
+    pub results: Vec<GameResult>,
+}
+
+/// The agents `paws tournament` plays against each other.
+pub fn registered_agents() -> Vec<Agent> {
+    vec![
+        Agent {
+            name: "random".to_string(),
+            strategy: ActionSelectionStrategy::Random,
+        },
+        Agent {
+            name: "greedy".to_string(),
+            strategy: ActionSelectionStrategy::Greedy,
+        },
+        Agent {
+            name: "mcts-100".to_string(),
+            strategy: ActionSelectionStrategy::Mcts {
+                budget: SearchBudget::Iterations(100),
+            },
+        },
+    ]
+}
+
+/// Why a tournament cannot be played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TournamentError {
+    NoAgents,
+    /// The rules are invalid or do not allow `num_players` players.
+    InvalidNumberOfPlayers {
+        num_players: usize,
+    },
+    /// The rules seat a solo automaton, which is not one of the agents and has no rating.
+    AutomatonSeat,
+}
+
+/// Plays `num_games` games of `num_players` seats. Game `g` is dealt from `seed + g`, and agents
+/// take turns in every seat: the lineup rotates by one agent every game, and is reversed every
+/// other round so that each pair of agents meets in both orders.
+pub fn run(
+    agents: &[Agent],
+    rules: &GameRules,
+    num_players: usize,
+    num_games: usize,
+    seed: u64,
+) -> Result<TournamentReport, TournamentError> {
+    if agents.is_empty() {
+        return Err(TournamentError::NoAgents);
+    }
+    if rules.validate().is_err() || !rules.allows(num_players) {
+        return Err(TournamentError::InvalidNumberOfPlayers { num_players });
+    }
+    if rules.seats(num_players) > num_players {
+        return Err(TournamentError::AutomatonSeat);
+    }
+    let results: Vec<GameResult> = (0..num_games)
+        .map(|g| {
+            let mut lineup: Vec<usize> = (0..num_players).map(|s| (g + s) % agents.len()).collect();
+            if (g / agents.len()) % 2 == 1 {
+                lineup.reverse();
+            }
+            play_game(agents, rules, lineup, seed.wrapping_add(g as u64))
+        })
+        .collect();
+    Ok(report(agents, results))
+}
+
+fn play_game(agents: &[Agent], rules: &GameRules, lineup: Vec<usize>, seed: u64) -> GameResult {
+    let mut game_table = GameTable::from_seed(
+        lineup.len(),
+        rules.clone(),
+        InitialPlayerSelectionStrategy::First,
+        QuestTileSelectionStrategy::Random,
+        seed,
+    )
+    .expect("Tournaments are played with a valid number of players");
+    for (player, &agent) in game_table.players.iter_mut().zip(&lineup) {
+        player.strategy = agents[agent].strategy.clone();
+    }
+    game_table.play_until_done();
+
+    let mut scores = vec![0; lineup.len()];
+    for player in &game_table.players {
+        scores[player.index] = player.score();
+    }
+    let winner_seats = match game_table.state {
+        GameState::Done { winner_indices } => winner_indices,
+        _ => unreachable!("The game was played until done"),
+    };
+    GameResult {
+        seed,
+        lineup,
+        scores,
+        winner_seats,
+    }
+}
+
+fn report(agents: &[Agent], results: Vec<GameResult>) -> TournamentReport {
+    let n = agents.len();
+    let ratings = elo(n, results.iter());
+    let mut rng = StdRng::seed_from_u64(results.first().map_or(0, |r| r.seed));
+    let mut samples: Vec<Vec<f64>> = vec![Vec::with_capacity(BOOTSTRAP_SAMPLES); n];
+    if !results.is_empty() {
+        for _ in 0..BOOTSTRAP_SAMPLES {
+            let resampled = (0..results.len()).map(|_| &results[rng.gen_range(0..results.len())]);
+            for (a, rating) in elo(n, resampled).into_iter().enumerate() {
+                samples[a].push(rating);
+            }
+        }
+    }
+
+    let mut head_to_head = vec![vec![0.0; n]; n];
+    let mut meetings = vec![vec![0; n]; n];
+    let mut games = vec![0; n];
+    let mut wins = vec![0.0; n];
+    for result in &results {
+        let mut seen = vec![false; n];
+        for (seat, &agent) in result.lineup.iter().enumerate() {
+            if !seen[agent] {
+                seen[agent] = true;
+                games[agent] += 1;
+            }
+            if result.winner_seats.contains(&seat) {
+                wins[agent] += 1.0 / result.winner_seats.len() as f64;
+            }
+        }
+        for_each_pair(result, |a, b, outcome| {
+            head_to_head[a][b] += outcome;
+            meetings[a][b] += 1;
+        });
+    }
+
+    let standings = agents
+        .iter()
+        .enumerate()
+        .map(|(a, agent)| {
+            samples[a].sort_by(f64::total_cmp);
+            let percentile = |p: f64| match samples[a].len() {
+                0 => ratings[a],
+                len => samples[a][((len - 1) as f64 * p).round() as usize],
+            };
+            AgentStanding {
+                name: agent.name.clone(),
+                games: games[a],
+                wins: wins[a],
+                rating: ratings[a],
+                rating_low: percentile(0.025),
+                rating_high: percentile(0.975),
+            }
+        })
+        .collect();
+    TournamentReport {
+        standings,
+        head_to_head,
+        meetings,
+        results,
+    }
+}
+
+/// Calls `f(a, b, outcome)` for every ordered pair of seats holding different agents, where
+/// `outcome` is 1 if `a` outscored `b`, 0.5 on a tie and 0 otherwise.
+fn for_each_pair(result: &GameResult, mut f: impl FnMut(usize, usize, f64)) {
+    for (i, &a) in result.lineup.iter().enumerate() {
+        for (j, &b) in result.lineup.iter().enumerate() {
+            if a == b {
+                continue;
+            }
+            let outcome = match result.scores[i].cmp(&result.scores[j]) {
+                std::cmp::Ordering::Greater => 1.0,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Less => 0.0,
+            };
+            f(a, b, outcome);
+        }
+    }
+}
+
+/// Multiplayer Elo: every game counts as a round robin between the agents at the table, with the
+/// K factor split across opponents so that bigger tables do not move ratings faster.
+fn elo<'a>(num_agents: usize, results: impl Iterator<Item = &'a GameResult>) -> Vec<f64> {
+    let mut ratings = vec![INITIAL_RATING; num_agents];
+    for result in results {
+        let opponents = (result.lineup.len() - 1).max(1) as f64;
+        let mut deltas = vec![0.0; num_agents];
+        for_each_pair(result, |a, b, outcome| {
+            let expected = 1.0 / (1.0 + 10f64.powf((ratings[b] - ratings[a]) / 400.0));
+            deltas[a] += K_FACTOR / opponents * (outcome - expected);
+        });
+        for (rating, delta) in ratings.iter_mut().zip(deltas) {
+            *rating += delta;
+        }
+    }
+    ratings
+}
+
+impl std::fmt::Display for TournamentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TournamentError::NoAgents => write!(f, "A tournament needs at least one agent"),
+            TournamentError::InvalidNumberOfPlayers { num_players } => {
+                write!(f, "Invalid number of players: {}", num_players)
+            }
+            TournamentError::AutomatonSeat => {
+                write!(f, "Tournaments cannot be played against the solo automaton")
+            }
+        }
+    }
+}
+
+impl TournamentReport {
+    /// One row per agent.
+    pub fn standings_csv(&self) -> String {
+        let mut csv = "agent,games,wins,win_rate,elo,elo_low,elo_high\n".to_string();
+        for s in &self.standings {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{:.4},{:.1},{:.1},{:.1}",
+                s.name,
+                s.games,
+                s.wins,
+                s.wins / s.games.max(1) as f64,
+                s.rating,
+                s.rating_low,
+                s.rating_high
+            );
+        }
+        csv
+    }
+
+    /// One row per pair of agents that met.
+    pub fn head_to_head_csv(&self) -> String {
+        let mut csv = "agent,opponent,meetings,score_rate\n".to_string();
+        for (a, row) in self.head_to_head.iter().enumerate() {
+            for (b, outcome) in row.iter().enumerate() {
+                if self.meetings[a][b] > 0 {
+                    let _ = writeln!(
+                        csv,
+                        "{},{},{},{:.4}",
+                        self.standings[a].name,
+                        self.standings[b].name,
+                        self.meetings[a][b],
+                        outcome / self.meetings[a][b] as f64
+                    );
+                }
+            }
+        }
+        csv
+    }
+
+    /// One row per seat of every game.
+    pub fn games_csv(&self) -> String {
+        let mut csv = "seed,seat,agent,score,won\n".to_string();
+        for result in &self.results {
+            for (seat, &agent) in result.lineup.iter().enumerate() {
+                let _ = writeln!(
+                    csv,
+                    "{},{},{},{},{}",
+                    result.seed,
+                    seat,
+                    self.standings[agent].name,
+                    result.scores[seat],
+                    result.winner_seats.contains(&seat)
+                );
+            }
+        }
+        csv
+    }
+
+    /// The standings by rating and the head to head rates, for a terminal.
+    pub fn table(&self) -> String {
+        let width = self
+            .standings
+            .iter()
+            .map(|s| s.name.len())
+            .max()
+            .unwrap_or(0)
+            .max(5);
+        let mut order: Vec<usize> = (0..self.standings.len()).collect();
+        order.sort_by(|&a, &b| {
+            self.standings[b]
+                .rating
+                .total_cmp(&self.standings[a].rating)
+        });
+
+        let mut table = String::new();
+        let _ = writeln!(
+            table,
+            "{:<width$}  {:>5}  {:>6}  {:>6}  {:>15}",
+            "agent", "games", "wins", "elo", "95% interval"
+        );
+        for &a in &order {
+            let s = &self.standings[a];
+            let _ = writeln!(
+                table,
+                "{:<width$}  {:>5}  {:>6.1}  {:>6.0}  {:>7.0}..{:<6.0}",
+                s.name, s.games, s.wins, s.rating, s.rating_low, s.rating_high
+            );
+        }
+        let _ = writeln!(table, "\nHead to head (row outscored column)");
+        let _ = write!(table, "{:<width$}", "");
+        for &b in &order {
+            let _ = write!(table, "  {:>width$}", self.standings[b].name);
+        }
+        let _ = writeln!(table);
+        for &a in &order {
+            let _ = write!(table, "{:<width$}", self.standings[a].name);
+            for &b in &order {
+                match self.meetings[a][b] {
+                    0 => {
+                        let _ = write!(table, "  {:>width$}", "-");
+                    }
+                    m => {
+                        let rate = format!("{:.0}%", 100.0 * self.head_to_head[a][b] / m as f64);
+                        let _ = write!(table, "  {:>width$}", rate);
+                    }
+                }
+            }
+            let _ = writeln!(table);
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bears::rules::SoloOpponent;
+
+    fn result(lineup: Vec<usize>, scores: Vec<usize>) -> GameResult {
+        let best = scores.iter().copied().max().unwrap_or(0);
+        GameResult {
+            seed: 0,
+            winner_seats: (0..scores.len()).filter(|&s| scores[s] == best).collect(),
+            lineup,
+            scores,
+        }
+    }
+
+    #[test]
+    fn elo_moves_ratings_by_the_surprise() {
+        let ratings = elo(2, [result(vec![0, 1], vec![5, 3])].iter());
+        assert!((ratings[0] - (INITIAL_RATING + K_FACTOR / 2.0)).abs() < 1e-9);
+        assert!((ratings[1] - (INITIAL_RATING - K_FACTOR / 2.0)).abs() < 1e-9);
+
+        let ratings = elo(2, [result(vec![0, 1], vec![4, 4])].iter());
+        assert_eq!(ratings, vec![INITIAL_RATING; 2]);
+
+        // A third agent splits the K factor, and ratings only ever move between agents.
+        let ratings = elo(3, [result(vec![0, 1, 2], vec![6, 3, 0])].iter());
+        assert!((ratings[0] - (INITIAL_RATING + K_FACTOR / 2.0)).abs() < 1e-9);
+        assert!((ratings.iter().sum::<f64>() - 3.0 * INITIAL_RATING).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bootstrap_intervals_surround_the_rating() {
+        let agents = &registered_agents()[..2];
+        let results = vec![result(vec![0, 1], vec![5, 3]); 20];
+        let consistent = report(agents, results);
+        for standing in &consistent.standings {
+            assert_eq!(standing.rating_low, standing.rating);
+            assert_eq!(standing.rating_high, standing.rating);
+        }
+        assert!(consistent.standings[0].rating > consistent.standings[1].rating);
+
+        let results = (0..20)
+            .map(|g| match g % 3 {
+                0 => result(vec![0, 1], vec![3, 5]),
+                _ => result(vec![1, 0], vec![3, 5]),
+            })
+            .collect();
+        let mixed = report(agents, results);
+        for standing in &mixed.standings {
+            assert!(standing.rating_low < standing.rating_high);
+            assert!(standing.rating_low <= standing.rating);
+            assert!(standing.rating <= standing.rating_high);
+        }
+    }
+
+    #[test]
+    fn needs_agents_and_a_valid_table() {
+        let rules = GameRules::default();
+        assert_eq!(
+            run(&[], &rules, 2, 4, 0).err(),
+            Some(TournamentError::NoAgents)
+        );
+        assert_eq!(
+            run(&registered_agents(), &rules, 7, 4, 0).err(),
+            Some(TournamentError::InvalidNumberOfPlayers { num_players: 7 })
+        );
+        let solo = GameRules {
+            solo_opponent: SoloOpponent::Automaton,
+            ..GameRules::default()
+        };
+        assert_eq!(
+            run(&registered_agents(), &solo, 1, 4, 0).err(),
+            Some(TournamentError::AutomatonSeat)
+        );
+        assert!(run(&registered_agents()[..1], &solo, 2, 2, 0).is_ok());
+    }
+}
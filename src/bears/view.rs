@@ -0,0 +1,395 @@
+//! What each seat, and each commentator, gets to see of a table.
+
+use super::models::*;
+use super::rules::GameRules;
+use super::system::{
+    greedy_choice, Action, GameState, GameTable, InitialPlayerSelectionStrategy, Player,
+    QuestTileSelectionStrategy,
+};
+use crate::utils::Deck;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+/// What everyone at the table knows about a player.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicPlayer {
+    /// The player's index, which stays the same wherever they sit.
+    pub index: usize,
+    pub bear: BearCard,
+    /// Number of cards in their hand, whose faces are hidden.
+    pub hand_size: usize,
+    /// Number of quest tiles they have yet to complete, whose faces are hidden.
+    pub quest_tile_count: usize,
+    /// Quests they completed, which score their reward.
+    pub completed_quests: Vec<QuestCard>,
+    /// The quest tiles among `completed_quests`.
+    pub completed_quest_tiles: Vec<QuestTile>,
+}
+
+/// The table as seen by a single player. Agents only ever receive views, so they cannot peek at
+/// opponents' hands and quest tiles or at the order of the decks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerView {
+    /// The rules the table plays by.
+    pub rules: GameRules,
+    /// The viewer.
+    pub player_index: usize,
+    /// The viewer's hand.
+    pub hand: Vec<ResourceCard>,
+    /// The viewer's quest tiles.
+    pub quest_tiles: Vec<QuestTile>,
+    /// Every player in turn order, including the viewer.
+    pub players: Vec<PublicPlayer>,
+    /// Quests anyone may complete, in the order they were revealed.
+    pub open_quests: Vec<QuestCard>,
+    /// Every card spent on quests since the last reshuffle, face up.
+    pub discard_pile: Deck<ResourceCard>,
+    /// Open quests that ran out of time in a solo game against a score target.
+    pub expired_quests: Vec<QuestCard>,
+    /// Cards left in the quest deck.
+    pub quests_remaining: usize,
+    /// Cards left in the resource deck.
+    pub resources_remaining: usize,
+    /// Quest tiles that were never dealt.
+    pub quest_tiles_remaining: usize,
+    /// Passes in a row so far. The game ends once every player has passed.
+    pub consecutive_passes: usize,
+    pub turns_played: usize,
+    pub state: GameState,
+    /// Empty unless it is the viewer's turn.
+    pub legal_actions: Vec<Action>,
+}
+
+/// A player with their hand and quest tiles face up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CasterPlayer {
+    pub index: usize,
+    pub bear: BearCard,
+    pub hand: Vec<ResourceCard>,
+    pub quest_tiles: Vec<QuestTile>,
+    /// Quests they completed, which score their reward.
+    pub completed_quests: Vec<QuestCard>,
+}
+
+/// The whole table, hidden cards and deck order included, as a commentator or a replay shows
+/// it. Never hand one to an agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CasterView {
+    pub rules: GameRules,
+    /// Every player in turn order.
+    pub players: Vec<CasterPlayer>,
+    pub open_quests: Vec<QuestCard>,
+    /// The quest deck, in draw order from the end.
+    pub quests: Deck<QuestCard>,
+    /// Quest tiles that were never dealt.
+    pub quest_tiles: Deck<QuestTile>,
+    /// The resource deck, in draw order from the end.
+    pub resources: Deck<ResourceCard>,
+    pub discard_pile: Deck<ResourceCard>,
+    pub expired_quests: Vec<QuestCard>,
+    pub consecutive_passes: usize,
+    pub turns_played: usize,
+    pub state: GameState,
+}
+
+impl PublicPlayer {
+    /// What everyone knows about `player`.
+    pub fn of(player: &Player) -> Self {
+        Self {
+            index: player.index,
+            bear: player.bear,
+            hand_size: player.hand.len(),
+            quest_tile_count: player.quest_tiles.len(),
+            completed_quests: player.completed_quests.clone(),
+            completed_quest_tiles: player.completed_quest_tiles.clone(),
+        }
+    }
+}
+
+impl CasterPlayer {
+    /// `player` with nothing hidden.
+    pub fn of(player: &Player) -> Self {
+        Self {
+            index: player.index,
+            bear: player.bear,
+            hand: player.hand.clone(),
+            quest_tiles: player.quest_tiles.clone(),
+            completed_quests: player.completed_quests.clone(),
+        }
+    }
+
+    /// Sum of the rewards of the completed quests.
+    pub fn score(&self) -> usize {
+        super::models::score(&self.completed_quests)
+    }
+}
+
+impl PlayerView {
+    /// The action of the greedy agent: the most rewarding quest the viewer can complete, or
+    /// else a draw.
+    pub fn greedy_action(&self) -> Action {
+        greedy_choice(&self.quest_tiles, &self.open_quests, &self.legal_actions)
+    }
+
+    /// Resource cards the viewer has not seen: everything not in the discard pile or their hand.
+    pub fn unseen_resources(&self) -> Vec<ResourceCard> {
+        let all: ResourceCounts = build_all_resources().into_iter().collect();
+        let seen: ResourceCounts = self
+            .discard_pile
+            .cards
+            .iter()
+            .chain(self.hand.iter())
+            .copied()
+            .collect();
+        all.difference(&seen).cards().collect()
+    }
+
+    /// A full table consistent with this view, with every hidden card dealt at random. Quest
+    /// cards and quest tiles are separate copies, so a completed quest tile only leaves the quest
+    /// tiles, and a completed or expired open quest only leaves the quest deck.
+    pub fn determinize(&self, rng: &mut impl Rng) -> GameTable {
+        let spent_tiles: Vec<QuestId> = self
+            .players
+            .iter()
+            .flat_map(|p| p.completed_quest_tiles.iter().map(|t| t.quest.id))
+            .collect();
+        let spent_quests: Vec<QuestId> = self
+            .players
+            .iter()
+            .flat_map(|p| completed_quest_cards(&p.completed_quests, &p.completed_quest_tiles))
+            .chain(self.expired_quests.iter().copied())
+            .map(|q| q.id)
+            .collect();
+        let (all_quests, all_tiles) = build_all_quests();
+        let mut hidden_quests: Vec<QuestCard> = all_quests
+            .into_iter()
+            .filter(|q| !spent_quests.contains(&q.id) && !self.open_quests.contains(q))
+            .collect();
+        let mut hidden_tiles: Vec<QuestTile> = all_tiles
+            .into_iter()
+            .filter(|t| !spent_tiles.contains(&t.quest.id) && !self.quest_tiles.contains(t))
+            .collect();
+        let mut hidden_resources = self.unseen_resources();
+        hidden_quests.shuffle(rng);
+        hidden_tiles.shuffle(rng);
+        hidden_resources.shuffle(rng);
+
+        let mut players: Vec<Player> = self
+            .players
+            .iter()
+            .map(|public| {
+                let mut player = Player::new(public.index, public.bear);
+                player.completed_quests = public.completed_quests.clone();
+                player.completed_quest_tiles = public.completed_quest_tiles.clone();
+                if public.index == self.player_index {
+                    player.hand = self.hand.clone();
+                    player.quest_tiles = self.quest_tiles.clone();
+                } else {
+                    player.hand = hidden_resources
+                        .split_off(hidden_resources.len().saturating_sub(public.hand_size));
+                    player.quest_tiles = hidden_tiles
+                        .split_off(hidden_tiles.len().saturating_sub(public.quest_tile_count));
+                }
+                player
+            })
+            .collect();
+        debug_assert_eq!(hidden_resources.len(), self.resources_remaining);
+        hidden_quests.truncate(self.quests_remaining);
+        hidden_tiles.truncate(self.quest_tiles_remaining);
+
+        // The players include a solo automaton's seat, which the rules do not count as a player.
+        let num_players = (1..=self.players.len())
+            .find(|&n| self.rules.seats(n) == self.players.len())
+            .expect("A view always has a valid number of seats");
+        let mut table = GameTable::from_seed(
+            num_players,
+            self.rules.clone(),
+            InitialPlayerSelectionStrategy::First,
+            QuestTileSelectionStrategy::Random,
+            rng.gen(),
+        )
+        .expect("A view always has a valid number of players");
+        for player in &mut players {
+            if let Some(seat) = table.players.iter().find(|p| p.index == player.index) {
+                player.strategy = seat.strategy.clone();
+            }
+        }
+        table.players = players;
+        table.quests = Deck::from(hidden_quests);
+        table.quest_tiles = Deck::from(hidden_tiles);
+        table.resources = Deck::from(hidden_resources);
+        table.open_quests = self.open_quests.clone();
+        table.discard_pile = self.discard_pile.clone();
+        table.expired_quests = self.expired_quests.clone();
+        table.state = self.state.clone();
+        table.consecutive_passes = self.consecutive_passes;
+        table.turns_played = self.turns_played;
+        table
+    }
+}
+
+/// The quest cards among `completed_quests`: each completed tile accounts for one completed
+/// quest of its id, so a quest completed both from the open quests and from a tile leaves one
+/// card.
+fn completed_quest_cards(
+    completed_quests: &[QuestCard],
+    completed_quest_tiles: &[QuestTile],
+) -> Vec<QuestCard> {
+    let mut cards = completed_quests.to_vec();
+    for tile in completed_quest_tiles {
+        if let Some(i) = cards.iter().position(|q| *q == tile.quest) {
+            cards.remove(i);
+        }
+    }
+    cards
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bears::rules::SoloOpponent;
+    use crate::bears::system::ActionSelectionStrategy;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn determinize_keeps_hidden_card_counts() {
+        let mut table = GameTable::from_seed(
+            3,
+            GameRules::default(),
+            InitialPlayerSelectionStrategy::Random,
+            QuestTileSelectionStrategy::Random,
+            5,
+        )
+        .unwrap();
+        for player in &mut table.players {
+            player.strategy = ActionSelectionStrategy::Greedy;
+        }
+        let mut rng = StdRng::seed_from_u64(9);
+        while table.current_player_index().is_some() || table.events.is_empty() {
+            table.play_one_step();
+            for player_index in 0..table.players.len() {
+                let view = table.view_for(player_index);
+                let game = view.determinize(&mut rng);
+                game.check_card_conservation().unwrap();
+                assert_eq!(game.quests.len(), table.quests.len());
+                assert_eq!(game.quest_tiles.len(), table.quest_tiles.len());
+                assert_eq!(game.resources.len(), table.resources.len());
+                for (sampled, real) in game.players.iter().zip(&table.players) {
+                    assert_eq!(sampled.index, real.index);
+                    assert_eq!(sampled.hand.len(), real.hand.len());
+                    assert_eq!(sampled.quest_tiles.len(), real.quest_tiles.len());
+                    if real.index == player_index {
+                        assert_eq!(sampled.hand, real.hand);
+                        assert_eq!(sampled.quest_tiles, real.quest_tiles);
+                    }
+                }
+
+                assert_quests_conserved(&game);
+            }
+        }
+    }
+
+    /// Checks that every quest card and every quest tile is in exactly one place.
+    fn assert_quests_conserved(game: &GameTable) {
+        let mut quests: Vec<QuestId> =
+            game.quests
+                .cards
+                .iter()
+                .chain(&game.open_quests)
+                .chain(&game.expired_quests)
+                .copied()
+                .chain(game.players.iter().flat_map(|p| {
+                    completed_quest_cards(&p.completed_quests, &p.completed_quest_tiles)
+                }))
+                .map(|q| q.id)
+                .collect();
+        let mut tiles: Vec<QuestId> = game
+            .quest_tiles
+            .cards
+            .iter()
+            .chain(game.players.iter().flat_map(|p| &p.quest_tiles))
+            .chain(game.players.iter().flat_map(|p| &p.completed_quest_tiles))
+            .map(|t| t.quest.id)
+            .collect();
+        let num_quests = quests.len();
+        let num_tiles = tiles.len();
+        quests.sort();
+        quests.dedup();
+        tiles.sort();
+        tiles.dedup();
+        assert_eq!(quests.len(), num_quests, "a quest card is duplicated");
+        assert_eq!(tiles.len(), num_tiles, "a quest tile is duplicated");
+    }
+
+    #[test]
+    fn completed_tiles_account_for_one_quest_each() {
+        let [a, b] = [0, 1].map(|id| QuestCard {
+            id: QuestId::new(id).unwrap(),
+        });
+        let tile = QuestTile { quest: a };
+        assert_eq!(completed_quest_cards(&[a, b, a], &[tile]), vec![b, a]);
+        assert_eq!(completed_quest_cards(&[a], &[tile]), vec![]);
+    }
+
+    #[test]
+    fn determinize_keeps_a_quest_completed_as_card_and_tile() {
+        let mut table = GameTable::from_seed(
+            3,
+            GameRules::default(),
+            InitialPlayerSelectionStrategy::Random,
+            QuestTileSelectionStrategy::Random,
+            5,
+        )
+        .unwrap();
+        table.play_one_step();
+        // Player 0 completes one of their tiles and the quest card of the same quest.
+        let player = &mut table.players[0];
+        let tile = player.quest_tiles.remove(0);
+        let card = match table.open_quests.iter().position(|q| *q == tile.quest) {
+            Some(i) => table.open_quests.remove(i),
+            None => table.quests.draw_first(|q| *q == tile.quest).unwrap(),
+        };
+        player.completed_quests.extend([card, tile.quest]);
+        player.completed_quest_tiles.push(tile);
+        assert_quests_conserved(&table);
+
+        let mut rng = StdRng::seed_from_u64(2);
+        for player_index in 0..table.players.len() {
+            let view = table.view_for(player_index);
+            for _ in 0..20 {
+                let game = view.determinize(&mut rng);
+                assert_quests_conserved(&game);
+                assert!(!game.quests.cards.contains(&card));
+            }
+        }
+    }
+
+    #[test]
+    fn determinize_keeps_the_solo_automaton() {
+        let rules = GameRules {
+            max_players: 1,
+            solo_opponent: SoloOpponent::Automaton,
+            ..GameRules::default()
+        };
+        let mut table = GameTable::from_seed(
+            1,
+            rules,
+            InitialPlayerSelectionStrategy::Random,
+            QuestTileSelectionStrategy::Random,
+            4,
+        )
+        .unwrap();
+        let mut rng = StdRng::seed_from_u64(3);
+        while table.current_player_index().is_some() || table.events.is_empty() {
+            table.play_one_step();
+            let game = table.view_for(0).determinize(&mut rng);
+            assert_eq!(game.players.len(), 2);
+            let automaton = game.players.iter().find(|p| p.index != 0).unwrap();
+            assert!(matches!(
+                automaton.strategy,
+                ActionSelectionStrategy::Greedy
+            ));
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! The game engine behind Pawlice Academy, for the `paws` binary and any other tool that wants
+//! to deal, play or analyse games.
+//!
+//! The stable surface is [`bears::models`] for the cards, [`bears::system`] for running a
+//! table, and [`utils::Deck`]. The other modules under [`bears`] hold the agents and analysis
+//! tools built on top of them.
+//!
+//! ```
+//! use pawlice_academy::bears::rules::GameRules;
+//! use pawlice_academy::bears::system::{
+//!     GameState, GameTable, InitialPlayerSelectionStrategy, QuestTileSelectionStrategy,
+//! };
+//!
+//! let mut table = GameTable::from_seed(
+//!     3,
+//!     GameRules::default(),
+//!     InitialPlayerSelectionStrategy::Random,
+//!     QuestTileSelectionStrategy::Random,
+//!     42,
+//! )
+//! .expect("3 players are allowed");
+//! table.play_until_done();
+//! assert!(matches!(table.state, GameState::Done { .. }));
+//! ```
+
+pub mod bears;
+pub mod utils;
@@ -1,12 +1,59 @@
 use std::process::ExitCode;
+use std::str::FromStr;
 
-mod bears;
-mod utils;
+use pawlice_academy::bears;
+
+#[cfg(feature = "tui")]
+mod tui;
 
 fn main() -> ExitCode {
-    let num_players = 5;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("tournament") => run_tournament(&args[1..]),
+        Some("balance") => run_balance(&args[1..]),
+        Some("challenge") => run_challenge(&args[1..]),
+        Some("serve") => run_server(&args[1..]),
+        Some("record") => run_record(&args[1..]),
+        Some("watch") => run_watch(&args[1..]),
+        Some("export-html") => run_export_html(&args[1..]),
+        #[cfg(feature = "tui")]
+        Some("tui") => run_tui(&args[1..]),
+        Some(command) if !command.starts_with("--") => {
+            println!("Unknown command: {}", command);
+            ExitCode::FAILURE
+        }
+        _ => play_game(&args),
+    }
+}
+
+fn play_game(args: &[String]) -> ExitCode {
+    let options = (|| {
+        let mut rules = rules(args)?;
+        if args.iter().any(|a| a == "--automaton") {
+            rules.solo_opponent = bears::rules::SoloOpponent::Automaton;
+        }
+        if let bears::rules::SoloOpponent::ScoreTarget(target) = rules.solo_opponent {
+            rules.solo_opponent =
+                bears::rules::SoloOpponent::ScoreTarget(option(args, "--target", target)?);
+        }
+        Ok::<_, String>((
+            rules,
+            option(args, "--players", 5usize)?,
+            agent(args, "--agent", "mcts")?,
+        ))
+    })();
+    let (rules, num_players, strategy) = match options {
+        Ok(options) => options,
+        Err(message) => {
+            println!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    // Only the seat playing `--agent` is advised; the other seats' odds are private.
+    let (advised, hint_draws) = (0, 3);
     let mut game_table = match bears::system::GameTable::from(
         num_players,
+        rules,
         bears::system::InitialPlayerSelectionStrategy::Random,
         bears::system::QuestTileSelectionStrategy::Random,
     ) {
@@ -16,31 +63,571 @@ fn main() -> ExitCode {
             return ExitCode::FAILURE;
         }
     };
+    game_table.players[advised].strategy = strategy;
+    let renderer = renderer(args).with_bears_of(&game_table);
     loop {
         match game_table.state {
-            bears::system::GameState::Done { winner_indices } => {
-                println!(
-                    "Done, Winner: {:?}",
-                    game_table
-                        .players
-                        .iter()
-                        .find(|p| winner_indices.contains(&p.index))
-                        .unwrap()
-                );
+            bears::system::GameState::Done { .. } => {
+                println!("{}", renderer.state(&game_table));
                 break;
             }
             _ => {
-                println!(
-                    "State: {:?}, Players: {:?}",
-                    game_table.state, game_table.players
-                );
+                println!("{}\n", renderer.table(&game_table));
+                if let bears::system::GameState::PlayerReady { player_index } = game_table.state {
+                    if player_index == advised {
+                        print_hint(&game_table, advised, hint_draws);
+                    }
+                }
                 game_table.play_one_step();
             }
         }
     }
     println!("---------Events---------");
     for event in game_table.events.iter() {
-        println!("{:?}", event);
+        println!("{}", renderer.event(event));
+    }
+    ExitCode::SUCCESS
+}
+
+fn print_hint(game_table: &bears::system::GameTable, player_index: usize, draws: usize) {
+    let view = game_table.view_for(player_index);
+    let player = game_table.player(player_index);
+    let unseen = view.unseen_resources();
+    let quests = view
+        .quest_tiles
+        .iter()
+        .map(|t| &t.quest)
+        .chain(view.open_quests.iter());
+    for quest in quests {
+        println!(
+            "Hint for {} bear (player {}): {:.1}% to complete {} ({}) within {} draws",
+            player.bear,
+            player_index,
+            100.0 * bears::odds::probability_to_complete(&view.hand, quest, &unseen, draws),
+            quest.name(),
+            quest.reward(),
+            draws
+        );
+    }
+}
+
+fn run_challenge(args: &[String]) -> ExitCode {
+    let options = (|| {
+        let challenge = match option(args, "--code", String::new())?.as_str() {
+            "" => bears::challenge::Challenge {
+                seed: option(args, "--seed", rand::random::<u32>() as u64)?,
+                turns: option(args, "--turns", 30usize)?,
+                target: option(args, "--target", 12usize)?,
+            },
+            code => code.parse()?,
+        };
+        // Without an agent the challenge is played interactively.
+        let agent = match args.iter().any(|a| a == "--agent") {
+            true => Some(agent(args, "--agent", "greedy")?),
+            false => None,
+        };
+        Ok::<_, String>((challenge, agent))
+    })();
+    let (challenge, agent) = match options {
+        Ok(options) => options,
+        Err(message) => {
+            println!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    println!(
+        "Challenge {}: reach {} reward within {} turns",
+        challenge, challenge.target, challenge.turns
+    );
+    let interactive = agent.is_none();
+    let renderer = renderer(args);
+    let mut table =
+        challenge.table(agent.unwrap_or(bears::system::ActionSelectionStrategy::Greedy));
+    let result = loop {
+        if let Some(result) = challenge.result(&table) {
+            break result;
+        }
+        if !interactive {
+            table.play_one_step();
+            continue;
+        }
+        let view = table.view_for(0);
+        println!(
+            "\nTurn {}/{}, score {}/{}",
+            view.turns_played + 1,
+            challenge.turns,
+            table.players[0].score(),
+            challenge.target
+        );
+        println!("Hand: {}", renderer.cards(&view.hand));
+        for tile in &view.quest_tiles {
+            println!("Quest tile: {}", renderer.quest(&tile.quest));
+        }
+        for quest in &view.open_quests {
+            println!("Open quest: {}", renderer.quest(quest));
+        }
+        for (i, action) in view.legal_actions.iter().enumerate() {
+            println!("  {}) {}", i + 1, describe(&renderer, &view, action));
+        }
+        let mut line = String::new();
+        match std::io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => return ExitCode::FAILURE,
+            Ok(_) => {}
+        }
+        match line.trim().parse::<usize>() {
+            Ok(choice) if (1..=view.legal_actions.len()).contains(&choice) => {
+                table.apply_action(view.legal_actions[choice - 1]);
+            }
+            _ => println!("Pick an action between 1 and {}", view.legal_actions.len()),
+        }
+    };
+    println!(
+        "\nScored {} of {} in {} turns: {} star{}",
+        result.score,
+        challenge.target,
+        result.turns_played,
+        result.stars,
+        if result.stars == 1 { "" } else { "s" }
+    );
+    println!(
+        "Share this challenge with: paws challenge --code {}",
+        challenge
+    );
+    ExitCode::SUCCESS
+}
+
+fn run_server(args: &[String]) -> ExitCode {
+    let options = (|| {
+        Ok::<_, String>((
+            rules(args)?,
+            option(args, "--players", 2usize)?,
+            option(args, "--port", 7878u16)?,
+            option(args, "--seed", rand::random::<u32>() as u64)?,
+            agent(args, "--bots", "greedy")?,
+        ))
+    })();
+    let (rules, num_players, port, seed, bot_strategy) = match options {
+        Ok(options) => options,
+        Err(message) => {
+            println!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let served = bears::server::Server::bind(("127.0.0.1", port), num_players, rules, seed)
+        .and_then(|mut server| {
+            server.bot_strategy = bot_strategy;
+            println!(
+                "Serving a {} player game on {}",
+                num_players,
+                server.local_addr()?
+            );
+            server.run()
+        });
+    match served {
+        Ok(table) => {
+            for player in &table.players {
+                println!(
+                    "{} bear (player {}) scored {}",
+                    player.bear,
+                    player.index,
+                    player.score()
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            println!("Server stopped: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_record(args: &[String]) -> ExitCode {
+    let Some(path) = args.first().filter(|a| !a.starts_with("--")) else {
+        println!("Usage: paws record <path> [--players N] [--seed S] [--agent random|greedy|mcts]");
+        return ExitCode::FAILURE;
+    };
+    let options = (|| {
+        Ok::<_, String>((
+            rules(args)?,
+            option(args, "--players", 3usize)?,
+            option(args, "--seed", rand::random::<u32>() as u64)?,
+            agent(args, "--agent", "greedy")?,
+        ))
+    })();
+    let (rules, num_players, seed, strategy) = match options {
+        Ok(options) => options,
+        Err(message) => {
+            println!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let Some(mut table) = bears::system::GameTable::from_seed(
+        num_players,
+        rules,
+        bears::system::InitialPlayerSelectionStrategy::Random,
+        bears::system::QuestTileSelectionStrategy::Random,
+        seed,
+    ) else {
+        println!("Invalid number of players: {}", num_players);
+        return ExitCode::FAILURE;
+    };
+    for player in table.players.iter_mut().take(num_players) {
+        player.strategy = strategy.clone();
+    }
+    let recording = bears::spectator::Recording::record(&mut table);
+    match recording.save(path) {
+        Ok(()) => {
+            println!(
+                "Recorded {} turns of seed {} to {}",
+                table.turns_played, seed, path
+            );
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            println!("Could not write {}: {}", path, error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+fn run_tui(args: &[String]) -> ExitCode {
+    let options = (|| {
+        Ok::<_, String>((
+            rules(args)?,
+            option(args, "--players", 3usize)?,
+            option(args, "--seed", rand::random::<u32>() as u64)?,
+            agent(args, "--agent", "greedy")?,
+        ))
+    })();
+    let (rules, num_players, seed, strategy) = match options {
+        Ok(options) => options,
+        Err(message) => {
+            println!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let Some(mut table) = bears::system::GameTable::from_seed(
+        num_players,
+        rules,
+        bears::system::InitialPlayerSelectionStrategy::Random,
+        bears::system::QuestTileSelectionStrategy::Random,
+        seed,
+    ) else {
+        println!("Invalid number of players: {}", num_players);
+        return ExitCode::FAILURE;
+    };
+    for player in table.players.iter_mut().take(num_players) {
+        player.strategy = strategy.clone();
+    }
+    let human = args.iter().any(|a| a == "--play").then_some(0);
+    match tui::App::new(table, human).run() {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(error) => {
+            println!("Terminal error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_watch(args: &[String]) -> ExitCode {
+    let Some(path) = args.first().filter(|a| !a.starts_with("--")) else {
+        println!("Usage: paws watch <recording> [--delay TURNS] [--player N | --caster]");
+        return ExitCode::FAILURE;
+    };
+    let options = (|| {
+        let audience = match optional(args, "--player")? {
+            _ if args.iter().any(|a| a == "--caster") => bears::spectator::Audience::Caster,
+            None => bears::spectator::Audience::Spectator,
+            Some(player_index) => bears::spectator::Audience::Player { player_index },
+        };
+        Ok::<_, String>((audience, option(args, "--delay", 0usize)?))
+    })();
+    let (audience, delay) = match options {
+        Ok(options) => options,
+        Err(message) => {
+            println!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let recording = match bears::spectator::Recording::load(path) {
+        Ok(recording) => recording,
+        Err(error) => {
+            println!("Could not read {}: {}", path, error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut renderer = renderer(args);
+    if let Some(frame) = recording.frames.first() {
+        renderer = renderer.with_bears(frame.table.players.iter().map(|p| (p.index, p.bear)));
+    }
+    let mut stream = bears::spectator::EventStream::new(audience, delay);
+    let mut log = Vec::new();
+    for frame in &recording.frames {
+        log.extend(frame.events.iter().cloned());
+        for event in stream.poll(&log) {
+            println!("{}", renderer.spectator_event(&event));
+        }
+        if audience == bears::spectator::Audience::Caster {
+            for player in &frame.table.players {
+                println!(
+                    "  {}: {} points, hand {}",
+                    renderer.name(player.index),
+                    player.score(),
+                    renderer.cards(&player.hand)
+                );
+            }
+        }
     }
     ExitCode::SUCCESS
 }
+
+fn run_export_html(args: &[String]) -> ExitCode {
+    let Some(path) = args.first().filter(|a| !a.starts_with("--")) else {
+        println!("Usage: paws export-html <recording> [--output PATH]");
+        return ExitCode::FAILURE;
+    };
+    let output = match html_output(path, args) {
+        Ok(output) => output,
+        Err(message) => {
+            println!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let recording = match bears::spectator::Recording::load(path) {
+        Ok(recording) => recording,
+        Err(error) => {
+            println!("Could not read {}: {}", path, error);
+            return ExitCode::FAILURE;
+        }
+    };
+    match std::fs::write(&output, bears::html::export(&recording)) {
+        Ok(()) => {
+            println!("Wrote {}", output);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            println!("Could not write {}: {}", output, error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Where `paws export-html` writes the page of the recording at `path`: `--output`, or else
+/// `<stem>.replay.html` next to the recording. Never the recording itself.
+fn html_output(path: &str, args: &[String]) -> Result<String, String> {
+    let default_output = std::path::Path::new(path)
+        .with_extension("replay.html")
+        .to_string_lossy()
+        .into_owned();
+    let output = option(args, "--output", default_output)?;
+    let same_file = match (std::fs::canonicalize(path), std::fs::canonicalize(&output)) {
+        (Ok(input), Ok(output)) => input == output,
+        _ => path == output,
+    };
+    match same_file {
+        true => Err(format!("Refusing to overwrite the recording {}", path)),
+        false => Ok(output),
+    }
+}
+
+fn describe(
+    renderer: &bears::render::Renderer,
+    view: &bears::view::PlayerView,
+    action: &bears::system::Action,
+) -> String {
+    match *action {
+        bears::system::Action::DrawResource => "Draw a resource".to_string(),
+        bears::system::Action::CompleteQuestTile { tile_index } => format!(
+            "Complete quest tile {}",
+            renderer.quest(&view.quest_tiles[tile_index].quest)
+        ),
+        bears::system::Action::CompleteOpenQuest { quest_index } => format!(
+            "Complete open quest {}",
+            renderer.quest(&view.open_quests[quest_index])
+        ),
+        bears::system::Action::Pass => "Pass".to_string(),
+    }
+}
+
+/// Colored output with `--color`, plain with `--no-color`, and otherwise colored on terminals.
+fn renderer(args: &[String]) -> bears::render::Renderer {
+    if args.iter().any(|a| a == "--color") {
+        bears::render::Renderer::colored()
+    } else if args.iter().any(|a| a == "--no-color") {
+        bears::render::Renderer::plain()
+    } else {
+        bears::render::Renderer::for_stdout()
+    }
+}
+
+/// Standard rules with any `--starting-resources`, `--quest-tiles` or `--open-quests` overrides.
+fn rules(args: &[String]) -> Result<bears::rules::GameRules, String> {
+    let standard = bears::rules::GameRules::default();
+    let rules = bears::rules::GameRules {
+        starting_resources: option(args, "--starting-resources", standard.starting_resources)?,
+        quest_tiles_per_player: option(args, "--quest-tiles", standard.quest_tiles_per_player)?,
+        open_quests: option(args, "--open-quests", standard.open_quests)?,
+        ..standard
+    };
+    rules.validate().map_err(|error| error.to_string())?;
+    Ok(rules)
+}
+
+/// The agent named after `--name` in `args`, one of `random`, `greedy` or `mcts`, with MCTS
+/// searching `--iterations` iterations per move.
+fn agent(
+    args: &[String],
+    name: &str,
+    default: &str,
+) -> Result<bears::system::ActionSelectionStrategy, String> {
+    match option(args, name, default.to_string())?.as_str() {
+        "random" => Ok(bears::system::ActionSelectionStrategy::Random),
+        "greedy" => Ok(bears::system::ActionSelectionStrategy::Greedy),
+        "mcts" => Ok(bears::system::ActionSelectionStrategy::Mcts {
+            budget: bears::mcts::SearchBudget::Iterations(option(args, "--iterations", 200)?),
+        }),
+        other => Err(format!("Unknown agent: {}", other)),
+    }
+}
+
+/// Value following `--name` in `args`, or `default` when the option is absent.
+fn option<T: FromStr>(args: &[String], name: &str, default: T) -> Result<T, String> {
+    Ok(optional(args, name)?.unwrap_or(default))
+}
+
+/// Value following `--name` in `args`, or `None` when the option is absent.
+fn optional<T: FromStr>(args: &[String], name: &str) -> Result<Option<T>, String> {
+    match args.iter().position(|a| a == name) {
+        None => Ok(None),
+        Some(i) => args
+            .get(i + 1)
+            .and_then(|v| v.parse().ok())
+            .map(Some)
+            .ok_or(format!("Invalid or missing value for {}", name)),
+    }
+}
+
+fn run_tournament(args: &[String]) -> ExitCode {
+    let options = (|| {
+        Ok::<_, String>((
+            rules(args)?,
+            option(args, "--games", 30usize)?,
+            option(args, "--players", 3usize)?,
+            option(args, "--seed", 0u64)?,
+            option(args, "--csv", String::new())?,
+        ))
+    })();
+    let (rules, num_games, num_players, seed, csv_dir) = match options {
+        Ok(options) => options,
+        Err(message) => {
+            println!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let agents = bears::tournament::registered_agents();
+    let report = match bears::tournament::run(&agents, &rules, num_players, num_games, seed) {
+        Ok(report) => report,
+        Err(error) => {
+            println!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    print!("{}", report.table());
+    if !csv_dir.is_empty() {
+        let dir = std::path::Path::new(&csv_dir);
+        let written = std::fs::create_dir_all(dir)
+            .and_then(|_| std::fs::write(dir.join("standings.csv"), report.standings_csv()))
+            .and_then(|_| std::fs::write(dir.join("head_to_head.csv"), report.head_to_head_csv()))
+            .and_then(|_| std::fs::write(dir.join("games.csv"), report.games_csv()));
+        if let Err(error) = written {
+            println!("Could not write CSV files to {}: {}", csv_dir, error);
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn run_balance(args: &[String]) -> ExitCode {
+    let options = (|| {
+        let rules = rules(args)?;
+        let num_players = option(args, "--players", 4usize)?;
+        let strategy = agent(args, "--strategy", "greedy")?;
+        let fixed_bears = match option(args, "--bears", String::new())?.as_str() {
+            "" => None,
+            names => Some(
+                names
+                    .split(',')
+                    .map(|name| {
+                        bears::models::BearCard::all()
+                            .into_iter()
+                            .find(|b| b.to_string().eq_ignore_ascii_case(name))
+                            .ok_or(format!("Unknown bear: {}", name))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+        };
+        Ok(bears::balance::BalanceSettings {
+            rules,
+            num_players,
+            num_games: option(args, "--games", 2000usize)?,
+            seed: option(args, "--seed", 0u64)?,
+            strategy,
+            initial_player_selection_strategy: match args.iter().any(|a| a == "--first") {
+                true => bears::system::InitialPlayerSelectionStrategy::First,
+                false => bears::system::InitialPlayerSelectionStrategy::Random,
+            },
+            fixed_bears,
+        })
+    })();
+    let report = options
+        .and_then(|settings| bears::balance::run(&settings).map_err(|error| error.to_string()));
+    match report {
+        Ok(report) => {
+            print!("{}", report.summary());
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            println!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn html_goes_next_to_the_recording() {
+        assert_eq!(
+            html_output("games/7.jsonl", &args(&["games/7.jsonl"])),
+            Ok("games/7.replay.html".to_string())
+        );
+        assert_eq!(
+            html_output("7.html", &args(&["7.html"])),
+            Ok("7.replay.html".to_string())
+        );
+    }
+
+    #[test]
+    fn html_never_overwrites_the_recording() {
+        let dir = std::env::temp_dir().join(format!("paws-html-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let recording = dir.join("game.html");
+        std::fs::write(&recording, "").unwrap();
+        let path = recording.to_string_lossy().into_owned();
+        let other_spelling = dir.join(".").join("game.html");
+        let output = other_spelling.to_string_lossy().into_owned();
+
+        assert!(html_output(&path, &args(&[&path, "--output", &path])).is_err());
+        assert!(html_output(&path, &args(&[&path, "--output", &output])).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,418 @@
+//! The full-screen terminal UI of `paws tui`: watch the agents play turn by turn, or take a seat
+//! and choose your own actions.
+
+use pawlice_academy::bears::models::ResourceCounts;
+use pawlice_academy::bears::render::Renderer;
+use pawlice_academy::bears::spectator::{Audience, SpectatorEvent};
+use pawlice_academy::bears::system::{Action, GameState, GameTable, Player};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::time::Duration;
+
+/// How long autoplay waits between turns.
+const AUTOPLAY_DELAY: Duration = Duration::from_millis(400);
+
+pub struct App {
+    table: GameTable,
+    /// The player the user plays, or `None` to watch every seat.
+    human: Option<usize>,
+    selected: usize,
+    autoplay: bool,
+    /// Log lines scrolled back from the newest.
+    scroll: usize,
+    quit: bool,
+}
+
+impl App {
+    /// `table` is set up if it is not already, and the other seats play until it is the human's
+    /// turn.
+    pub fn new(mut table: GameTable, human: Option<usize>) -> Self {
+        if matches!(table.state, GameState::Uninitialized) {
+            table.play_one_step();
+        }
+        let mut app = Self {
+            table,
+            human,
+            selected: 0,
+            autoplay: false,
+            scroll: 0,
+            quit: false,
+        };
+        app.play_others();
+        app
+    }
+
+    /// Runs the UI until the user quits. The terminal is restored even if drawing fails.
+    pub fn run(mut self) -> io::Result<GameTable> {
+        let mut terminal = ratatui::init();
+        let result = self.event_loop(&mut terminal);
+        ratatui::restore();
+        result.map(|()| self.table)
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.render(frame))?;
+            let timeout = match self.autoplay && !self.is_done() {
+                true => AUTOPLAY_DELAY,
+                false => Duration::from_secs(60),
+            };
+            if event::poll(timeout)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key.code);
+                    }
+                }
+            } else if self.autoplay {
+                self.step();
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        let actions = self.human_actions();
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::PageUp => self.scroll += 5,
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_sub(5),
+            KeyCode::Up if !actions.is_empty() => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if !actions.is_empty() => {
+                self.selected = (self.selected + 1).min(actions.len() - 1)
+            }
+            KeyCode::Enter if !actions.is_empty() => {
+                if let Some(&action) = actions.get(self.selected) {
+                    self.table.apply_action(action);
+                    self.selected = 0;
+                    self.scroll = 0;
+                    self.play_others();
+                }
+            }
+            KeyCode::Char(' ') | KeyCode::Char('n') if self.human.is_none() => self.step(),
+            KeyCode::Char('a') if self.human.is_none() => self.autoplay = !self.autoplay,
+            _ => {}
+        }
+    }
+
+    /// Lets the current player's agent take one turn.
+    fn step(&mut self) {
+        self.table.play_one_step();
+        self.scroll = 0;
+    }
+
+    /// Lets the agents play until it is the human's turn.
+    fn play_others(&mut self) {
+        let Some(human) = self.human else { return };
+        while let Some(player_index) = self.table.current_player_index() {
+            if player_index == human {
+                break;
+            }
+            self.table.play_one_step();
+        }
+    }
+
+    /// The actions the human may choose from right now.
+    fn human_actions(&self) -> Vec<Action> {
+        match self.human {
+            Some(human) if self.table.current_player_index() == Some(human) => {
+                self.table.legal_actions()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        matches!(self.table.state, GameState::Done { .. })
+    }
+
+    fn audience(&self) -> Audience {
+        match self.human {
+            Some(player_index) => Audience::Player { player_index },
+            None => Audience::Caster,
+        }
+    }
+
+    fn render(&self, frame: &mut Frame) {
+        let actions = self.human_actions();
+        let [status, players, market, log, controls] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(12),
+            Constraint::Length(self.table.open_quests.len() as u16 + 3),
+            Constraint::Min(6),
+            Constraint::Length(match actions.is_empty() {
+                true => 1,
+                false => actions.len() as u16 + 3,
+            }),
+        ])
+        .areas(frame.area());
+
+        frame.render_widget(
+            Paragraph::new(
+                Renderer::plain()
+                    .with_bears_of(&self.table)
+                    .state(&self.table),
+            )
+            .bold(),
+            status,
+        );
+        self.render_players(frame, players);
+        self.render_market(frame, market);
+        self.render_log(frame, log);
+        match actions.is_empty() {
+            true => frame.render_widget(Paragraph::new(self.help_line()).dark_gray(), controls),
+            false => self.render_actions(frame, controls, &actions),
+        }
+    }
+
+    fn help_line(&self) -> &'static str {
+        match (self.human, self.autoplay) {
+            (Some(_), _) => "q quit · PgUp/PgDn scroll log",
+            (None, false) => "space step · a autoplay · q quit · PgUp/PgDn scroll log",
+            (None, true) => "a pause · q quit · PgUp/PgDn scroll log",
+        }
+    }
+
+    fn render_players(&self, frame: &mut Frame, area: Rect) {
+        let players = &self.table.players;
+        let columns = Layout::horizontal(vec![
+            Constraint::Ratio(1, players.len() as u32);
+            players.len()
+        ])
+        .split(area);
+        for (player, column) in players.iter().zip(columns.iter()) {
+            let revealed = self.human.is_none_or(|human| human == player.index);
+            let mut block = Block::bordered().title(format!(
+                " {} bear (player {}) · {} ",
+                player.bear,
+                player.index,
+                player.score()
+            ));
+            if self.table.current_player_index() == Some(player.index) {
+                block = block.border_style(Style::new().fg(Color::Yellow));
+            }
+            let paragraph = Paragraph::new(player_lines(player, revealed))
+                .block(block)
+                .wrap(Wrap { trim: false });
+            frame.render_widget(paragraph, *column);
+        }
+    }
+
+    fn render_market(&self, frame: &mut Frame, area: Rect) {
+        let table = &self.table;
+        let mut lines: Vec<Line> = table
+            .open_quests
+            .iter()
+            .map(|quest| Line::from(Renderer::plain().quest(quest)))
+            .collect();
+        lines.push(
+            Line::from(format!(
+                "Quest deck {} · Resource deck {} · Discard pile {}",
+                table.quests.len(),
+                table.resources.len(),
+                table.discard_pile.len()
+            ))
+            .dark_gray(),
+        );
+        let block = Block::bordered().title(" Open quests ");
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn render_log(&self, frame: &mut Frame, area: Rect) {
+        let audience = self.audience();
+        let renderer = Renderer::plain().with_bears_of(&self.table);
+        let lines: Vec<Line> = self
+            .table
+            .events
+            .iter()
+            .filter_map(|event| SpectatorEvent::of(event, audience))
+            .map(|event| Line::from(renderer.spectator_event(&event)))
+            .collect();
+        let height = area.height.saturating_sub(2) as usize;
+        let scroll = self.scroll.min(lines.len().saturating_sub(height));
+        let end = lines.len() - scroll;
+        let start = end.saturating_sub(height);
+        let title = match scroll {
+            0 => " Events ".to_string(),
+            _ => format!(" Events ({} newer) ", scroll),
+        };
+        let paragraph =
+            Paragraph::new(lines[start..end].to_vec()).block(Block::bordered().title(title));
+        frame.render_widget(paragraph, area);
+    }
+
+    fn render_actions(&self, frame: &mut Frame, area: Rect, actions: &[Action]) {
+        let human = self
+            .table
+            .player(self.human.expect("Only humans choose actions"));
+        let items: Vec<ListItem> = actions
+            .iter()
+            .map(|action| ListItem::new(action_text(&self.table, human, action)))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(" Your move · ↑/↓ choose · enter play · q quit "))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+}
+
+fn player_lines(player: &Player, revealed: bool) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(format!("Hand ({})", player.hand.len())).bold()];
+    match revealed {
+        true => {
+            let counts: ResourceCounts = player.hand.iter().copied().collect();
+            lines.extend(counts.iter().map(|(card, count)| {
+                Line::from(format!("  {}× {}", count, Renderer::plain().card(&card)))
+            }));
+        }
+        false => lines.push(Line::from("  hidden").dark_gray()),
+    }
+    lines.push(Line::from(format!("Quest tiles ({})", player.quest_tiles.len())).bold());
+    match revealed {
+        true => lines.extend(
+            player
+                .quest_tiles
+                .iter()
+                .map(|tile| Line::from(format!("  {}", Renderer::plain().quest(&tile.quest)))),
+        ),
+        false => lines.push(Line::from("  hidden").dark_gray()),
+    }
+    lines.push(Line::from(format!("Completed ({})", player.completed_quests.len())).bold());
+    lines.extend(player.completed_quests.iter().map(|quest| {
+        Line::from(vec![
+            Span::raw(format!("  {} ", quest.name())),
+            Span::raw(format!("+{}", quest.reward())).green(),
+        ])
+    }));
+    lines
+}
+
+fn action_text(table: &GameTable, player: &Player, action: &Action) -> String {
+    match *action {
+        Action::DrawResource => format!("Draw a resource ({} left)", table.resources.len()),
+        Action::CompleteQuestTile { tile_index } => format!(
+            "Complete quest tile {}",
+            Renderer::plain().quest(&player.quest_tiles[tile_index].quest)
+        ),
+        Action::CompleteOpenQuest { quest_index } => format!(
+            "Complete open quest {}",
+            Renderer::plain().quest(&table.open_quests[quest_index])
+        ),
+        Action::Pass => "Pass".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pawlice_academy::bears::rules::GameRules;
+    use pawlice_academy::bears::system::{
+        ActionSelectionStrategy, InitialPlayerSelectionStrategy, QuestTileSelectionStrategy,
+    };
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn app(human: Option<usize>) -> App {
+        let mut table = GameTable::from_seed(
+            3,
+            GameRules::default(),
+            InitialPlayerSelectionStrategy::Random,
+            QuestTileSelectionStrategy::Random,
+            11,
+        )
+        .unwrap();
+        for player in &mut table.players {
+            player.strategy = ActionSelectionStrategy::Greedy;
+        }
+        App::new(table, human)
+    }
+
+    fn screen(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 60)).unwrap();
+        terminal.draw(|frame| app.render(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let width = buffer.area.width as usize;
+        let symbols: Vec<&str> = buffer.content.iter().map(|cell| cell.symbol()).collect();
+        symbols
+            .chunks(width)
+            .map(|row| row.concat())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn others_play_until_the_humans_turn() {
+        for human in 0..3 {
+            let app = app(Some(human));
+            assert_eq!(app.table.current_player_index(), Some(human));
+            assert!(!app.human_actions().is_empty());
+        }
+    }
+
+    #[test]
+    fn selection_stays_within_the_actions() {
+        let mut app = app(Some(1));
+        let actions = app.human_actions().len();
+        for _ in 0..actions + 5 {
+            app.handle_key(KeyCode::Down);
+        }
+        assert_eq!(app.selected, actions - 1);
+        for _ in 0..actions + 5 {
+            app.handle_key(KeyCode::Up);
+        }
+        assert_eq!(app.selected, 0);
+    }
+
+    #[test]
+    fn enter_plays_the_selected_action() {
+        let mut app = app(Some(1));
+        app.handle_key(KeyCode::Down);
+        let turns = app.table.turns_played;
+        app.handle_key(KeyCode::Enter);
+        // The human's turn and then both agents' turns.
+        assert_eq!(app.table.turns_played, turns + 3);
+        assert_eq!(app.table.current_player_index(), Some(1));
+        assert_eq!(app.selected, 0);
+    }
+
+    #[test]
+    fn watching_steps_on_request() {
+        let mut app = app(None);
+        assert!(app.human_actions().is_empty());
+        let turns = app.table.turns_played;
+        app.handle_key(KeyCode::Char(' '));
+        assert_eq!(app.table.turns_played, turns + 1);
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.table.turns_played, turns + 1);
+        app.handle_key(KeyCode::Char('a'));
+        assert!(app.autoplay);
+        app.handle_key(KeyCode::Char('q'));
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn log_scroll_stops_at_the_first_event() {
+        let mut app = app(None);
+        for _ in 0..30 {
+            app.handle_key(KeyCode::Char(' '));
+        }
+        for _ in 0..100 {
+            app.handle_key(KeyCode::PageUp);
+        }
+        let oldest = screen(&app);
+        assert!(oldest.contains("Playing with"), "{}", oldest);
+        assert!(oldest.contains("newer) "), "{}", oldest);
+
+        for _ in 0..200 {
+            app.handle_key(KeyCode::PageDown);
+        }
+        assert_eq!(app.scroll, 0);
+        assert!(screen(&app).contains(" Events "));
+    }
+}
@@ -1,48 +1,237 @@
-use rand::seq::SliceRandom;
+//! Helpers that are not specific to the bears.
 
-#[derive(Debug, Clone)]
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+/// A stack of cards. The top of the deck is the end of `cards`, so drawing pops from the back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Deck<Card> {
+    /// The cards from the bottom of the deck to the top.
     pub cards: Vec<Card>,
 }
 
-#[allow(dead_code)]
+impl<Card> Default for Deck<Card> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<Card> Deck<Card> {
+    /// An empty deck.
     pub fn new() -> Self {
         Self { cards: Vec::new() }
     }
 
+    /// A deck of `cards`, with the last card on top.
     pub fn from(cards: Vec<Card>) -> Self {
         Self { cards }
     }
 
+    /// Whether there are no cards left.
     pub fn is_empty(&self) -> bool {
         self.cards.is_empty()
     }
 
+    /// Number of cards in the deck.
     pub fn len(&self) -> usize {
         self.cards.len()
     }
 
-    pub fn shuffle(&mut self) {
-        self.cards.shuffle(&mut rand::thread_rng());
+    /// Shuffles with `rng`, so that a seeded game always shuffles the same way.
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng);
     }
 
+    /// Puts `card` on top of the deck.
     pub fn place(&mut self, card: Card) {
         self.cards.push(card);
     }
 
+    /// Draws the top card, or `None` if the deck is empty.
     pub fn draw(&mut self) -> Option<Card> {
         self.cards.pop()
     }
 
+    /// Draws the card at `index`, counted from the bottom of the deck, or `None` if there is
+    /// no such card.
     pub fn draw_from(&mut self, index: usize) -> Option<Card> {
-        match self.len() {
-            0 => None,
-            _ => Some(self.cards.remove(index)),
+        if index >= self.len() {
+            return None;
         }
+        Some(self.cards.remove(index))
     }
 
+    /// Keeps the bottom `len` cards and throws away the rest.
     pub fn truncate(&mut self, len: usize) {
         self.cards.truncate(len);
     }
+
+    /// The top `n` cards, in the order they would be drawn.
+    pub fn peek(&self, n: usize) -> impl Iterator<Item = &Card> {
+        self.cards.iter().rev().take(n)
+    }
+
+    /// Draws up to `n` cards, in draw order.
+    pub fn draw_n(&mut self, n: usize) -> Vec<Card> {
+        let at = self.len().saturating_sub(n);
+        let mut drawn = self.cards.split_off(at);
+        drawn.reverse();
+        drawn
+    }
+
+    /// Puts `card` at the bottom of the deck, to be drawn last.
+    pub fn place_at_bottom(&mut self, card: Card) {
+        self.cards.insert(0, card);
+    }
+
+    /// Deals `per_hand` cards to each of `hands` hands, one card at a time around the table.
+    /// Returns `None`, leaving the deck untouched, if there are not enough cards.
+    pub fn deal(&mut self, hands: usize, per_hand: usize) -> Option<Vec<Vec<Card>>> {
+        if self.len() < hands * per_hand {
+            return None;
+        }
+        let mut dealt: Vec<Vec<Card>> = (0..hands).map(|_| Vec::with_capacity(per_hand)).collect();
+        for _ in 0..per_hand {
+            for hand in dealt.iter_mut() {
+                hand.extend(self.draw());
+            }
+        }
+        Some(dealt)
+    }
+
+    /// Moves the top `n` cards to the bottom, keeping their order.
+    pub fn cut(&mut self, n: usize) {
+        let n = n.min(self.len());
+        self.cards.rotate_right(n);
+    }
+
+    /// Cuts the deck at a random depth.
+    pub fn cut_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let n = rng.gen_range(0..=self.len());
+        self.cut(n);
+    }
+
+    /// Shows the top `n` cards, in draw order, and returns them to the top of the deck in the
+    /// same order, so they are drawn next.
+    pub fn reveal_and_return(&mut self, n: usize) -> Vec<Card>
+    where
+        Card: Clone,
+    {
+        let revealed = self.draw_n(n);
+        self.cards.extend(revealed.iter().rev().cloned());
+        revealed
+    }
+
+    /// Draws the card closest to the top that matches `predicate`, or `None`, leaving the deck
+    /// untouched, if no card does. This is the draw-by-predicate counterpart of `draw_from`.
+    pub fn draw_first(&mut self, predicate: impl Fn(&Card) -> bool) -> Option<Card> {
+        let index = self.cards.iter().rposition(predicate)?;
+        Some(self.cards.remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// A deck of distinct cards `0..len`, so lost or duplicated cards show up.
+    fn deck(len: usize) -> Deck<usize> {
+        Deck::from((0..len).collect())
+    }
+
+    /// Asserts that `deck` and the cards taken out of it are exactly `0..len`.
+    fn conserved<'a>(len: usize, deck: &Deck<usize>, taken: impl IntoIterator<Item = &'a usize>) {
+        let mut cards: Vec<usize> = deck
+            .cards
+            .iter()
+            .copied()
+            .chain(taken.into_iter().copied())
+            .collect();
+        cards.sort_unstable();
+        assert_eq!(cards, (0..len).collect::<Vec<_>>());
+    }
+
+    proptest! {
+        #[test]
+        fn draw_conserves_cards(len in 0..40usize) {
+            let mut deck = deck(len);
+            let drawn = deck.draw();
+            prop_assert_eq!(drawn, len.checked_sub(1));
+            conserved(len, &deck, &drawn);
+        }
+
+        #[test]
+        fn draw_n_conserves_cards(len in 0..40usize, n in 0..60usize) {
+            let mut deck = deck(len);
+            let drawn = deck.draw_n(n);
+            prop_assert_eq!(drawn.len(), n.min(len));
+            prop_assert!(drawn.windows(2).all(|w| w[0] > w[1]));
+            conserved(len, &deck, &drawn);
+        }
+
+        #[test]
+        fn draw_from_conserves_cards(len in 0..40usize, index in 0..60usize) {
+            let mut deck = deck(len);
+            let drawn = deck.draw_from(index);
+            prop_assert_eq!(drawn, (index < len).then_some(index));
+            conserved(len, &deck, &drawn);
+        }
+
+        #[test]
+        fn draw_first_conserves_cards(len in 0..40usize, divisor in 1..60usize) {
+            let mut deck = deck(len);
+            let drawn = deck.draw_first(|card| card % divisor == 0 && *card > 0);
+            prop_assert_eq!(drawn, (1..len).rev().find(|card| card % divisor == 0));
+            conserved(len, &deck, &drawn);
+        }
+
+        #[test]
+        fn deal_conserves_cards(len in 0..40usize, hands in 0..8usize, per_hand in 0..8usize) {
+            let mut deck = deck(len);
+            match deck.deal(hands, per_hand) {
+                Some(dealt) => {
+                    prop_assert!(dealt.iter().all(|hand| hand.len() == per_hand));
+                    prop_assert_eq!(dealt.len(), hands);
+                    conserved(len, &deck, dealt.iter().flatten());
+                }
+                None => {
+                    prop_assert!(len < hands * per_hand);
+                    prop_assert_eq!(deck.cards, (0..len).collect::<Vec<_>>());
+                }
+            }
+        }
+
+        #[test]
+        fn cuts_conserve_cards(len in 0..40usize, n in 0..60usize, seed: u64) {
+            let mut deck = deck(len);
+            deck.cut(n);
+            conserved(len, &deck, &[]);
+            if len > 0 {
+                let top = (len - n.min(len) + len - 1) % len;
+                prop_assert_eq!(deck.peek(1).next(), Some(&top));
+            }
+            deck.cut_with(&mut StdRng::seed_from_u64(seed));
+            conserved(len, &deck, &[]);
+        }
+
+        #[test]
+        fn place_at_bottom_conserves_cards(len in 0..40usize) {
+            let mut deck = Deck::from((1..=len).collect());
+            deck.place_at_bottom(0);
+            prop_assert_eq!(deck.cards.first(), Some(&0));
+            conserved(len + 1, &deck, &[]);
+        }
+
+        #[test]
+        fn reveal_and_return_leaves_the_deck_as_it_was(len in 0..40usize, n in 0..60usize) {
+            let mut deck = deck(len);
+            let revealed = deck.reveal_and_return(n);
+            prop_assert_eq!(&revealed, &(len.saturating_sub(n)..len).rev().collect::<Vec<_>>());
+            prop_assert_eq!(&deck.cards, &(0..len).collect::<Vec<_>>());
+            prop_assert_eq!(deck.draw_n(n), revealed);
+        }
+    }
 }
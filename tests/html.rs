@@ -0,0 +1,41 @@
+//! Exporting recorded games as HTML pages.
+
+use pawlice_academy::bears::html;
+use pawlice_academy::bears::rules::GameRules;
+use pawlice_academy::bears::spectator::Recording;
+use pawlice_academy::bears::system::{
+    GameTable, InitialPlayerSelectionStrategy, QuestTileSelectionStrategy,
+};
+
+#[test]
+fn exported_pages_are_self_contained_and_cover_every_turn() {
+    let mut table = GameTable::from_seed(
+        3,
+        GameRules::default(),
+        InitialPlayerSelectionStrategy::Random,
+        QuestTileSelectionStrategy::Random,
+        21,
+    )
+    .unwrap();
+    let recording = Recording::record(&mut table);
+    let page = html::export(&recording);
+
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.trim_end().ends_with("</html>"));
+    assert!(!page.contains("<script src") && !page.contains("<link"));
+    assert_eq!(page.matches("<svg").count(), 2);
+    assert_eq!(page.matches("<polyline").count(), 2 * table.players.len());
+    assert_eq!(
+        page.matches("<tr class=\"turn").count(),
+        recording.frames.len()
+    );
+    for player in &table.players {
+        assert!(page.contains(&format!("{} bear", player.bear)));
+    }
+}
+
+#[test]
+fn empty_recordings_still_export() {
+    let page = html::export(&Recording::default());
+    assert!(page.contains("The recording is empty."));
+}
@@ -0,0 +1,68 @@
+//! Text rendering of cards, players, tables and events.
+
+use pawlice_academy::bears::models::{
+    FortuneKind, IngredientKind, MaterialKind, QuestCard, QuestId, ResourceCard,
+};
+use pawlice_academy::bears::render::Renderer;
+use pawlice_academy::bears::rules::GameRules;
+use pawlice_academy::bears::system::{
+    GameTable, InitialPlayerSelectionStrategy, LoggedEvents, QuestTileSelectionStrategy,
+};
+
+#[test]
+fn hands_are_grouped_by_kind() {
+    let hand = [
+        ResourceCard::Ingredient(IngredientKind::Fish),
+        ResourceCard::Material(MaterialKind::Snowball),
+        ResourceCard::Ingredient(IngredientKind::Fish),
+        ResourceCard::Fortune(FortuneKind::BeeAttack),
+    ];
+    assert_eq!(
+        Renderer::plain().cards(&hand),
+        "2× Fish, Snowball, Bee Attack"
+    );
+    assert_eq!(Renderer::plain().cards(&[]), "nothing");
+}
+
+#[test]
+fn colors_are_only_added_when_asked_for() {
+    let quest = QuestCard {
+        id: QuestId::new(0).unwrap(),
+    };
+    assert!(!Renderer::plain().quest(&quest).contains('\x1b'));
+    let colored = Renderer::colored().quest(&quest);
+    assert!(colored.contains("\x1b[34mFish\x1b[0m"));
+    assert_ne!(
+        Renderer::colored().card(&ResourceCard::Ingredient(IngredientKind::Wheat)),
+        Renderer::colored().card(&ResourceCard::Ingredient(IngredientKind::Rice))
+    );
+}
+
+#[test]
+fn events_name_players_by_bear_once_known() {
+    let mut table = GameTable::from_seed(
+        2,
+        GameRules::default(),
+        InitialPlayerSelectionStrategy::First,
+        QuestTileSelectionStrategy::Random,
+        1,
+    )
+    .unwrap();
+    table.play_until_done();
+    let passed = LoggedEvents::Passed { player_index: 1 };
+    assert_eq!(passed.to_string(), "Player 1 passed");
+    let renderer = Renderer::plain().with_bears_of(&table);
+    assert_eq!(
+        renderer.event(&passed),
+        format!("{} bear passed", table.player(1).bear)
+    );
+    for event in &table.events {
+        assert!(!renderer.event(event).is_empty());
+    }
+
+    let summary = table.to_string();
+    assert!(summary.starts_with("Game over"));
+    for player in &table.players {
+        assert!(summary.contains(&player.to_string()));
+    }
+}
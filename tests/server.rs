@@ -0,0 +1,212 @@
+//! Plays networked games on localhost with in-process clients.
+
+use pawlice_academy::bears::models::BearCard;
+use pawlice_academy::bears::rules::GameRules;
+use pawlice_academy::bears::server::{Client, ClientMessage, Server, ServerMessage};
+use pawlice_academy::bears::spectator::SpectatorEvent;
+use pawlice_academy::bears::system::{Action, GameState, LoggedEvents};
+use pawlice_academy::bears::view::PlayerView;
+use std::thread;
+
+fn start(num_players: usize, seed: u64) -> (String, thread::JoinHandle<GameState>) {
+    let server = Server::bind("127.0.0.1:0", num_players, GameRules::default(), seed).unwrap();
+    let addr = server.local_addr().unwrap().to_string();
+    (addr, thread::spawn(move || server.run().unwrap().state))
+}
+
+/// Joins `seat`, returning the client, its player index and its reconnection token.
+fn join(addr: &str, seat: Option<usize>) -> (Client, usize, String) {
+    let mut client = Client::connect(addr).unwrap();
+    client.send(&ClientMessage::Join { seat }).unwrap();
+    match client.recv().unwrap() {
+        ServerMessage::Joined {
+            player_index,
+            token,
+        } => (client, player_index, token),
+        other => panic!("Unexpected {:?}", other),
+    }
+}
+
+/// Skips lobby updates until the next view.
+fn next_view(client: &mut Client) -> Box<PlayerView> {
+    loop {
+        match client.recv().unwrap() {
+            ServerMessage::View { view } => return view,
+            ServerMessage::Lobby { .. } => {}
+            other => panic!("Unexpected {:?}", other),
+        }
+    }
+}
+
+/// Plays greedily if it is the viewer's turn.
+fn act(client: &mut Client, view: &PlayerView) {
+    if !view.legal_actions.is_empty() {
+        let action = view.greedy_action();
+        client.send(&ClientMessage::Act { action }).unwrap();
+    }
+}
+
+/// Plays greedily until the game is done, returning the final scores.
+fn play(mut client: Client) -> Vec<usize> {
+    loop {
+        match client.recv().unwrap() {
+            ServerMessage::View { view } => act(&mut client, &view),
+            ServerMessage::Done { scores, .. } => return scores,
+            ServerMessage::Error { message } => panic!("{}", message),
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn clients_play_a_game_to_the_end() {
+    let (addr, server) = start(3, 11);
+    let players: Vec<_> = (0..3)
+        .map(|_| {
+            let addr = addr.clone();
+            thread::spawn(move || {
+                let (mut client, _, _) = join(&addr, None);
+                client.send(&ClientMessage::Ready { ready: true }).unwrap();
+                play(client)
+            })
+        })
+        .collect();
+    let scores: Vec<Vec<usize>> = players.into_iter().map(|p| p.join().unwrap()).collect();
+    assert!(scores.windows(2).all(|w| w[0] == w[1]));
+    assert!(matches!(server.join().unwrap(), GameState::Done { .. }));
+}
+
+#[test]
+fn server_refuses_moves_out_of_turn_and_illegal_moves() {
+    let (addr, _server) = start(2, 5);
+    let mut clients: Vec<Client> = (0..2)
+        .map(|seat| {
+            let (client, player_index, _) = join(&addr, Some(seat));
+            assert_eq!(player_index, seat);
+            client
+        })
+        .collect();
+    for client in clients.iter_mut() {
+        client.send(&ClientMessage::Ready { ready: true }).unwrap();
+    }
+    let views: Vec<_> = clients.iter_mut().map(next_view).collect();
+    let current = views
+        .iter()
+        .position(|v| !v.legal_actions.is_empty())
+        .expect("Someone has the first turn");
+
+    let waiting = &mut clients[1 - current];
+    waiting
+        .send(&ClientMessage::Act {
+            action: Action::DrawResource,
+        })
+        .unwrap();
+    assert!(matches!(
+        waiting.recv().unwrap(),
+        ServerMessage::Error { .. }
+    ));
+
+    let mover = &mut clients[current];
+    let illegal = Action::CompleteOpenQuest { quest_index: 99 };
+    mover.send(&ClientMessage::Act { action: illegal }).unwrap();
+    assert!(matches!(mover.recv().unwrap(), ServerMessage::Error { .. }));
+    mover
+        .send(&ClientMessage::Act {
+            action: Action::DrawResource,
+        })
+        .unwrap();
+    assert!(matches!(mover.recv().unwrap(), ServerMessage::View { .. }));
+}
+
+#[test]
+fn bots_fill_the_table_and_picked_bears_are_kept() {
+    let (addr, server) = start(4, 3);
+    let (mut client, player_index, _) = join(&addr, None);
+    client
+        .send(&ClientMessage::PickBear {
+            bear: Some(BearCard::Panda),
+        })
+        .unwrap();
+    client.send(&ClientMessage::FillWithBots).unwrap();
+    client.send(&ClientMessage::Ready { ready: true }).unwrap();
+    let view = next_view(&mut client);
+    let me = view
+        .players
+        .iter()
+        .find(|p| p.index == player_index)
+        .unwrap();
+    assert_eq!(me.bear, BearCard::Panda);
+    assert_eq!(view.players.len(), 4);
+    act(&mut client, &view);
+    play(client);
+    assert!(matches!(server.join().unwrap(), GameState::Done { .. }));
+}
+
+#[test]
+fn reconnecting_restores_the_hidden_state() {
+    let (addr, _server) = start(2, 8);
+    let (mut client, player_index, token) = join(&addr, None);
+    client.send(&ClientMessage::FillWithBots).unwrap();
+    client.send(&ClientMessage::Ready { ready: true }).unwrap();
+    let before = next_view(&mut client);
+    drop(client);
+
+    let mut client = Client::connect(&addr).unwrap();
+    client.send(&ClientMessage::Reconnect { token }).unwrap();
+    match client.recv().unwrap() {
+        ServerMessage::Joined {
+            player_index: seat, ..
+        } => assert_eq!(seat, player_index),
+        other => panic!("Unexpected {:?}", other),
+    }
+    let after = next_view(&mut client);
+    assert_eq!(after.hand, before.hand);
+    assert_eq!(after.quest_tiles, before.quest_tiles);
+
+    let mut stranger = Client::connect(&addr).unwrap();
+    stranger
+        .send(&ClientMessage::Reconnect {
+            token: "nope".to_string(),
+        })
+        .unwrap();
+    assert!(matches!(
+        stranger.recv().unwrap(),
+        ServerMessage::Error { .. }
+    ));
+}
+
+#[test]
+fn spectators_receive_public_events_until_the_end() {
+    let (addr, server) = start(2, 12);
+    let mut spectator = Client::connect(&addr).unwrap();
+    spectator
+        .send(&ClientMessage::Spectate { delay: 1 })
+        .unwrap();
+    let (mut client, _, _) = join(&addr, None);
+    client.send(&ClientMessage::FillWithBots).unwrap();
+    client.send(&ClientMessage::Ready { ready: true }).unwrap();
+    let player = thread::spawn(move || play(client));
+
+    let mut events = Vec::new();
+    loop {
+        match spectator.recv().unwrap() {
+            ServerMessage::Events { events: released } => events.extend(released),
+            ServerMessage::Done { .. } => break,
+            other => panic!("Unexpected {:?}", other),
+        }
+    }
+    player.join().unwrap();
+    assert!(matches!(server.join().unwrap(), GameState::Done { .. }));
+    assert!(!events.iter().any(|e| matches!(
+        e,
+        SpectatorEvent::Revealed {
+            event: LoggedEvents::DrewResource { .. } | LoggedEvents::BuiltResourceDeck { .. }
+        }
+    )));
+    assert!(matches!(
+        events.last(),
+        Some(SpectatorEvent::Revealed {
+            event: LoggedEvents::Finished { .. }
+        })
+    ));
+}
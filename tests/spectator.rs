@@ -0,0 +1,113 @@
+//! Event visibility, delayed spectator streams and recordings.
+
+use pawlice_academy::bears::rules::GameRules;
+use pawlice_academy::bears::spectator::{Audience, EventStream, Recording, SpectatorEvent};
+use pawlice_academy::bears::system::{
+    GameTable, InitialPlayerSelectionStrategy, LoggedEvents, QuestTileSelectionStrategy, Visibility,
+};
+
+fn table(seed: u64) -> GameTable {
+    GameTable::from_seed(
+        3,
+        GameRules::default(),
+        InitialPlayerSelectionStrategy::Random,
+        QuestTileSelectionStrategy::Random,
+        seed,
+    )
+    .unwrap()
+}
+
+fn revealed(events: &[SpectatorEvent]) -> Vec<&LoggedEvents> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            SpectatorEvent::Revealed { event } => Some(event),
+            SpectatorEvent::Hidden { .. } => None,
+        })
+        .collect()
+}
+
+#[test]
+fn spectators_only_see_public_events() {
+    let mut table = table(2);
+    table.play_until_done();
+    let events = EventStream::new(Audience::Spectator, 0).poll(&table.events);
+    assert!(revealed(&events)
+        .iter()
+        .all(|e| e.visibility() == Visibility::Public));
+    let draws = table
+        .events
+        .iter()
+        .filter(|e| matches!(e, LoggedEvents::DrewResource { .. }))
+        .count();
+    let hidden = events
+        .iter()
+        .filter(|e| matches!(e, SpectatorEvent::Hidden { .. }))
+        .count();
+    assert_eq!(hidden, draws);
+    assert!(matches!(
+        revealed(&events).last(),
+        Some(LoggedEvents::Finished { .. })
+    ));
+}
+
+#[test]
+fn players_see_their_own_draws_and_casters_see_everything() {
+    let mut table = table(6);
+    table.play_until_done();
+    let events = EventStream::new(Audience::Player { player_index: 1 }, 0).poll(&table.events);
+    for event in &events {
+        match event {
+            SpectatorEvent::Revealed {
+                event: LoggedEvents::DrewResource { player_index, .. },
+            } => assert_eq!(*player_index, 1),
+            SpectatorEvent::Hidden { player_index } => assert_ne!(*player_index, 1),
+            _ => {}
+        }
+    }
+    let caster = EventStream::new(Audience::Caster, 0).poll(&table.events);
+    assert_eq!(revealed(&caster).len(), table.events.len());
+}
+
+#[test]
+fn delayed_streams_hold_back_turns_until_the_game_is_done() {
+    let mut table = table(9);
+    table.play_one_step();
+    let mut stream = EventStream::new(Audience::Spectator, 2);
+    let setup = stream.poll(&table.events);
+    assert!(!setup.is_empty());
+
+    table.play_one_step();
+    table.play_one_step();
+    assert!(stream.poll(&table.events).is_empty());
+    table.play_one_step();
+    assert_eq!(stream.poll(&table.events).len(), 1);
+
+    table.play_until_done();
+    let rest = stream.poll(&table.events);
+    assert!(matches!(
+        revealed(&rest).last(),
+        Some(LoggedEvents::Finished { .. })
+    ));
+    assert!(stream.poll(&table.events).is_empty());
+}
+
+#[test]
+fn recordings_round_trip_through_a_file() {
+    let mut table = table(4);
+    let recording = Recording::record(&mut table);
+    assert_eq!(recording.events().count(), table.events.len());
+    let last = &recording.frames.last().unwrap().table;
+    assert_eq!(last.turns_played, table.turns_played);
+
+    let path = std::env::temp_dir().join(format!("pawlice-recording-{}.jsonl", std::process::id()));
+    recording.save(&path).unwrap();
+    let loaded = Recording::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.frames.len(), recording.frames.len());
+    let scores = |r: &Recording| -> Vec<usize> {
+        let table = &r.frames.last().unwrap().table;
+        table.players.iter().map(|p| p.score()).collect()
+    };
+    assert_eq!(scores(&loaded), scores(&recording));
+}